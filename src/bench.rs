@@ -0,0 +1,57 @@
+use crate::{
+    board::Board,
+    converters::fen_converter::fen_to_board,
+    gamestate::GameState,
+    search::{Engine, SearchLimits},
+};
+use std::{
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+pub const BENCH_DEPTH: u8 = 10;
+
+// a fixed suite: the node count of "bench" changes only when the search does
+pub const BENCH_POSITIONS: [&str; 12] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 8",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r2q1rk1/pb1nbppp/1p2pn2/2pp4/3P4/1P1BPN2/PBPN1PPP/R2Q1RK1 w - - 0 10",
+    "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1",
+    "2rq1rk1/pp1bppbp/2np1np1/8/3NP3/1BN1BP2/PPPQ2PP/2KR3R b - - 0 11",
+    "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1",
+    "r1b1kb1r/3q1ppp/pBp1pn2/8/Np3P2/5B2/PPP3PP/R2Q1RK1 w kq - 0 1",
+    "4r1k1/p1qr1p2/2pb1Bp1/1p5p/3P1n1R/1B3P2/PP3PK1/2Q4R w - - 0 1",
+    "8/8/8/4k3/8/8/2PK4/8 w - - 0 1",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+];
+
+// searches every position of the suite to "depth" from a fresh state and prints the
+// total, so two builds can be compared by their node counts
+pub fn run_bench(engine: &mut Engine, depth: u8) -> u64 {
+    let own_book: bool = engine.own_book;
+    engine.own_book = false;
+    engine.signals.stop.store(false, Ordering::Relaxed);
+    engine.signals.infinite.store(false, Ordering::Relaxed);
+    engine.signals.pondering.store(false, Ordering::Relaxed);
+
+    let start: Instant = Instant::now();
+    let mut total_nodes: u64 = 0;
+    for fen in BENCH_POSITIONS {
+        let (mut board, mut state): (Board, GameState) = fen_to_board(fen);
+        board.total_occupancy();
+        board.update_full_cache();
+        engine.new_game();
+        engine.side = state.whose_turn;
+        engine.depth = depth;
+        engine.find_best_move(&board, &mut state, SearchLimits::depth(depth));
+        total_nodes += engine.searched_nodes;
+    }
+    engine.own_book = own_book;
+
+    let elapsed: Duration = start.elapsed();
+    let nps: u64 = (total_nodes as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+    println!("{total_nodes} nodes {nps} nps\r");
+    return total_nodes;
+}
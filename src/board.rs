@@ -1,4 +1,8 @@
-use crate::{board_geometry_templates::*, constants::masks::BIT_MASKS, gamestate::GameState};
+use crate::{
+    board_geometry_templates::*,
+    constants::{heuristics::HEURISTICS_TABLES, masks::BIT_MASKS},
+    gamestate::GameState,
+};
 // standard representation: 0b0000000000000000000000000000000000000000000000000000000000000000 (binary)
 #[derive(Clone, Debug, PartialEq)]
 pub struct Board {
@@ -10,6 +14,8 @@ pub struct Board {
 
     pub white_king_square: u8,
     pub black_king_square: u8,
+
+    pub king_phases: usize, // index into HEURISTICS_TABLES, see Engine::update_king_heuristics
 }
 
 impl Board {
@@ -35,8 +41,16 @@ impl Board {
             cached_pieces: [0; 64],
             white_king_square: 4,
             black_king_square: 60,
+            king_phases: 0,
         };
     }
+
+    // piece tables matching the current phase of both kings
+    #[inline(always)]
+    pub fn heuristics(&self) -> &'static [[i32; 64]; 12] {
+        return &HEURISTICS_TABLES[self.king_phases];
+    }
+
     pub fn update_full_cache(&mut self) {
         for square in 0..64 {
             let mask: u64 = BIT_MASKS[square];
@@ -116,7 +130,19 @@ impl Board {
 
     #[inline(always)]
     pub fn is_capture(&self, m: u16) -> bool {
-        return self.piece_at(to_square(m)) != 0;
+        // castling lands on its own rook, which is not a capture
+        return self.piece_at(to_square(m)) != 0 && !is_castling(m);
+    }
+
+    // no sequence of moves mates: the kings alone, or with a single knight or bishop
+    #[inline(always)]
+    pub fn is_insufficient_material(&self) -> bool {
+        let (bbs, minors): (&[u64; 12], u64) = (
+            &self.bitboards,
+            self.bitboards[1] | self.bitboards[2] | self.bitboards[7] | self.bitboards[8],
+        );
+        return bbs[0] | bbs[3] | bbs[4] | bbs[6] | bbs[9] | bbs[10] == 0
+            && minors.count_ones() < 2;
     }
 
     pub fn calculate_check_restrictions(&self, _state: &mut GameState, _for_color: u16) -> () {
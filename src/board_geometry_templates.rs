@@ -83,6 +83,12 @@ pub const BLACK_LONG: u8 = 8; // all available = 15
 pub const NOT_WHITE_CASTLING: u8 = !(WHITE_SHORT | WHITE_LONG);
 pub const NOT_BLACK_CASTLING: u8 = !(BLACK_SHORT | BLACK_LONG);
 
+// a1 to a1 can never be played, so it marks a passed turn in the moves history
+pub const NULL_MOVE: u16 = 0;
+
+// the rook squares of WHITE_SHORT, WHITE_LONG, BLACK_SHORT and BLACK_LONG in standard chess
+pub const STANDARD_CASTLING_ROOKS: [u8; 4] = [7, 0, 63, 56];
+
 pub const WHITE_SHORT_MASK: u8 = 0b1;
 pub const WHITE_LONG_MASK: u8 = 0b10;
 pub const BLACK_SHORT_MASK: u8 = 0b100;
@@ -96,6 +102,37 @@ pub fn from_square(m: u16) -> u8 {
 pub fn to_square(m: u16) -> u16 {
     return (m & TO_MASK) >> TO_SHIFT;
 }
+// castling is encoded as the king taking its own rook, which works for chess960 as well
+#[inline(always)]
+pub fn is_castling(m: u16) -> bool {
+    let flag: u16 = m >> MARK_SHIFT;
+    return flag == 1 || flag == 8;
+}
+// (king destination, rook destination): g and f files for the short castling, c and d for the long one
+#[inline(always)]
+pub fn castling_destinations(king_square: u8, rook_square: u8) -> (u8, u8) {
+    let rank_start: u8 = king_square & 56;
+    return if rook_square > king_square {
+        (rank_start + 6, rank_start + 5)
+    } else {
+        (rank_start + 2, rank_start + 3)
+    };
+}
+// all the squares from `a` to `b` inclusive, both on the same rank
+#[inline(always)]
+pub fn rank_span(a: u8, b: u8) -> u64 {
+    let (low, high): (u8, u8) = if a < b { (a, b) } else { (b, a) };
+    return (u64::MAX >> (63 - high)) & (u64::MAX << low);
+}
+// markers 7-13 are 0-6 with the check added
+#[inline(always)]
+pub fn without_check_flag(m: u16) -> u16 {
+    return if (m >> MARK_SHIFT) >= 7 {
+        m - (7 << MARK_SHIFT)
+    } else {
+        m
+    };
+}
 
 #[inline(always)]
 pub fn get_bb_index(piece_type: u16, color: &u16) -> (usize, usize) {
@@ -9,7 +9,7 @@ use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
 pub const KNIGHT_ATTACKS: [u64; 64] = knight_attacks();
-pub const COORDS_TO_INDICES: Lazy<HashMap<String, u8>> = Lazy::new(|| {
+pub static COORDS_TO_INDICES: Lazy<HashMap<String, u8>> = Lazy::new(|| {
     (0..=63)
         .map(|i: u8| {
             let (col, row) = (i as u32 / 8 + 1, i as u32 % 8);
@@ -22,7 +22,7 @@ pub const COORDS_TO_INDICES: Lazy<HashMap<String, u8>> = Lazy::new(|| {
         })
         .collect()
 });
-pub const INDICES_TO_COORDS: Lazy<HashMap<u8, String>> = Lazy::new(|| {
+pub static INDICES_TO_COORDS: Lazy<HashMap<u8, String>> = Lazy::new(|| {
     (0..=63)
         .map(|i: u8| {
             let (col, row) = (i as u32 / 8 + 1, i as u32 % 8);
@@ -259,8 +259,10 @@ pub fn generate_blockers(mask: u64) -> Vec<u64> {
 
     return blockers;
 }
-pub static mut ROOK_ATTACKS: [u64; 64 * 4096] = [0; 64 * 4096];
-pub static mut BISHOP_ATTACKS: [u64; 64 * 512] = [0; 64 * 512];
+// built at compile time, like the other tables, so nothing has to be initialized before use
+#[allow(long_running_const_eval)]
+pub static ROOK_ATTACKS: [u64; 64 * 4096] = sliding_attacks::<{ 64 * 4096 }>(true);
+pub static BISHOP_ATTACKS: [u64; 64 * 512] = sliding_attacks::<{ 64 * 512 }>(false);
 
 pub const ROOK_OFFSETS: [usize; 64] = {
     let mut offsets: [usize; 64] = [0; 64];
@@ -282,7 +284,7 @@ pub const BISHOP_OFFSETS: [usize; 64] = {
     offsets
 };
 
-pub fn rook_attacks_with_blockers(square: usize, blockers: u64) -> u64 {
+pub const fn rook_attacks_with_blockers(square: usize, blockers: u64) -> u64 {
     let (rank, file) = (square / 8, square % 8);
     let mut attacks: u64 = 0;
 
@@ -329,7 +331,7 @@ pub fn rook_attacks_with_blockers(square: usize, blockers: u64) -> u64 {
     return attacks;
 }
 
-pub fn bishop_attacks_with_blockers(square: usize, blockers: u64) -> u64 {
+pub const fn bishop_attacks_with_blockers(square: usize, blockers: u64) -> u64 {
     let (rank, file) = (square / 8, square % 8);
     let mut attacks: u64 = 0;
 
@@ -380,37 +382,36 @@ pub fn bishop_attacks_with_blockers(square: usize, blockers: u64) -> u64 {
     return attacks;
 }
 
-pub fn initialize_sliding_attack_tables() -> () {
+// every subset of the mask is a possible blocker configuration, visited with the carry-rippler trick
+const fn sliding_attacks<const LEN: usize>(is_rook: bool) -> [u64; LEN] {
+    let mut table: [u64; LEN] = [0; LEN];
     let mut square: usize = 0;
-    unsafe {
-        while square < 64 {
-            let rook_mask: u64 = ROOK_MASKS[square];
-            let rook_blockers: Vec<u64> = generate_blockers(rook_mask);
-
-            let bishop_mask: u64 = BISHOP_MASKS[square];
-            let bishop_blockers: Vec<u64> = generate_blockers(bishop_mask);
-
-            let mut blockers_index: usize = 0;
-            while blockers_index < rook_blockers.len() {
-                let idx: usize = ((rook_blockers[blockers_index].wrapping_mul(ROOK_MAGICS[square]))
-                    >> ROOK_SHIFTS[square]) as usize;
-                ROOK_ATTACKS[ROOK_OFFSETS[square] + idx] =
-                    rook_attacks_with_blockers(square, rook_blockers[blockers_index]);
-                blockers_index += 1;
-            }
-
-            blockers_index = 0;
-            while blockers_index < bishop_blockers.len() {
-                let idx: usize = ((bishop_blockers[blockers_index]
-                    .wrapping_mul(BISHOP_MAGICS[square]))
+    while square < 64 {
+        let mask: u64 = if is_rook {
+            ROOK_MASKS[square]
+        } else {
+            BISHOP_MASKS[square]
+        };
+        let mut blockers: u64 = 0;
+        loop {
+            if is_rook {
+                let idx: usize =
+                    (blockers.wrapping_mul(ROOK_MAGICS[square]) >> ROOK_SHIFTS[square]) as usize;
+                table[ROOK_OFFSETS[square] + idx] = rook_attacks_with_blockers(square, blockers);
+            } else {
+                let idx: usize = (blockers.wrapping_mul(BISHOP_MAGICS[square])
                     >> BISHOP_SHIFTS[square]) as usize;
-                BISHOP_ATTACKS[BISHOP_OFFSETS[square] + idx] =
-                    bishop_attacks_with_blockers(square, bishop_blockers[blockers_index]);
-                blockers_index += 1;
+                table[BISHOP_OFFSETS[square] + idx] =
+                    bishop_attacks_with_blockers(square, blockers);
+            }
+            blockers = blockers.wrapping_sub(mask) & mask;
+            if blockers == 0 {
+                break;
             }
-            square += 1;
         }
+        square += 1;
     }
+    return table;
 }
 
 #[inline(always)]
@@ -420,7 +421,7 @@ pub fn rook_attacks(initial_pos: usize, occupancy: u64) -> u64 {
         >> ROOK_SHIFTS[initial_pos]) as usize;
     let offset: usize = ROOK_OFFSETS[initial_pos];
 
-    return unsafe { ROOK_ATTACKS[offset + idx] };
+    return ROOK_ATTACKS[offset + idx];
 }
 
 #[inline(always)]
@@ -430,37 +431,41 @@ pub fn bishop_attacks(initial_pos: usize, occupancy: u64) -> u64 {
         >> BISHOP_SHIFTS[initial_pos]) as usize;
     let offset: usize = BISHOP_OFFSETS[initial_pos];
 
-    return unsafe { BISHOP_ATTACKS[offset + idx] };
+    return BISHOP_ATTACKS[offset + idx];
 }
 
-pub static mut RAYS_BETWEEN: [[u64; 64]; 64] = [[0; 64]; 64]; // between two squares
-pub static mut RAYS_FROM: [[u64; 64]; 64] = [[0; 64]; 64]; // from the last square to the end of line/diagonal
+pub static RAYS_BETWEEN: [[u64; 64]; 64] = compute_all_rays(); // between two squares
+pub static RAYS_FROM: [[u64; 64]; 64] = compute_all_rays_from(); // from the last square to the end of line/diagonal
 
-pub fn compute_all_rays() -> () {
+const fn compute_all_rays() -> [[u64; 64]; 64] {
     let mut rays: [[u64; 64]; 64] = [[0u64; 64]; 64];
-    for sq1 in 0..64 {
-        for sq2 in 0..64 {
+    let mut sq1: usize = 0;
+    while sq1 < 64 {
+        let mut sq2: usize = 0;
+        while sq2 < 64 {
             rays[sq1][sq2] = compute_ray_between(sq1 as u8, sq2 as u8);
+            sq2 += 1;
         }
+        sq1 += 1;
     }
-    unsafe {
-        RAYS_BETWEEN = rays;
-    }
+    return rays;
 }
 
-pub fn compute_all_rays_from() -> () {
+const fn compute_all_rays_from() -> [[u64; 64]; 64] {
     let mut rays: [[u64; 64]; 64] = [[0; 64]; 64];
-    for sq1 in 0..64 {
-        for sq2 in 0..64 {
+    let mut sq1: usize = 0;
+    while sq1 < 64 {
+        let mut sq2: usize = 0;
+        while sq2 < 64 {
             rays[sq1][sq2] = compute_ray_from(sq1 as u8, sq2 as u8);
+            sq2 += 1;
         }
+        sq1 += 1;
     }
-    unsafe {
-        RAYS_FROM = rays;
-    }
+    return rays;
 }
 
-fn compute_ray_between(sq1: u8, sq2: u8) -> u64 {
+const fn compute_ray_between(sq1: u8, sq2: u8) -> u64 {
     if sq1 == sq2 {
         return 0;
     }
@@ -504,7 +509,7 @@ fn compute_ray_between(sq1: u8, sq2: u8) -> u64 {
 }
 
 // Compute ray from sq1 through sq2 and beyond
-fn compute_ray_from(sq1: u8, sq2: u8) -> u64 {
+const fn compute_ray_from(sq1: u8, sq2: u8) -> u64 {
     if sq1 == sq2 {
         return 0;
     }
@@ -547,14 +552,17 @@ fn compute_ray_from(sq1: u8, sq2: u8) -> u64 {
     return ray;
 }
 
-pub static mut TWO_SQUARES_LINE: [[u64; 64]; 64] = [[0; 64]; 64]; // entire line/diagonal that the squares share
+pub static TWO_SQUARES_LINE: [[u64; 64]; 64] = compute_all_lines(); // entire line/diagonal that the squares share
 
-pub fn compute_all_lines() -> () {
+const fn compute_all_lines() -> [[u64; 64]; 64] {
     let mut lines: [[u64; 64]; 64] = [[0; 64]; 64];
 
-    for sq1 in 0..64 {
-        for sq2 in 0..64 {
+    let mut sq1: usize = 0;
+    while sq1 < 64 {
+        let mut sq2: usize = 0;
+        while sq2 < 64 {
             if sq1 == sq2 {
+                sq2 += 1;
                 continue;
             }
 
@@ -606,12 +614,11 @@ pub fn compute_all_lines() -> () {
                     f += step_f;
                 }
             }
+            sq2 += 1;
         }
+        sq1 += 1;
     }
-
-    unsafe {
-        TWO_SQUARES_LINE = lines;
-    }
+    return lines;
 }
 
 pub const MVV_LVA: [[i16; 6]; 6] = {
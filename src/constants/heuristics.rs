@@ -122,20 +122,38 @@ pub const BLACK_KING_HEURISTICS: [i32; 64] = {
     temp
 };
 
-pub static mut HEURISTICS_TABLE: [[i32; 64]; 12] = [
-    WHITE_PAWN_HEURISTICS,
-    WHITE_KNIGHT_HEURISTICS,
-    WHITE_BISHOP_HEURISTICS,
-    WHITE_ROOK_HEURISTICS,
-    WHITE_QUEEN_HEURISTICS,
-    WHITE_KING_HEURISTICS,
-    BLACK_PAWN_HEURISTICS,
-    BLACK_KNIGHT_HEURISTICS,
-    BLACK_BISHOP_HEURISTICS,
-    BLACK_ROOK_HEURISTICS,
-    BLACK_QUEEN_HEURISTICS,
-    BLACK_KING_HEURISTICS,
-];
+// bits of Board::king_phases, set when that king uses its endgame table
+pub const WHITE_KING_ENDGAME: usize = 1;
+pub const BLACK_KING_ENDGAME: usize = 2;
+
+// piece tables for every combination of the kings' phases, indexed by Board::king_phases
+pub static HEURISTICS_TABLES: [[[i32; 64]; 12]; 4] = {
+    let mut tables: [[[i32; 64]; 12]; 4] = [[
+        WHITE_PAWN_HEURISTICS,
+        WHITE_KNIGHT_HEURISTICS,
+        WHITE_BISHOP_HEURISTICS,
+        WHITE_ROOK_HEURISTICS,
+        WHITE_QUEEN_HEURISTICS,
+        WHITE_KING_HEURISTICS,
+        BLACK_PAWN_HEURISTICS,
+        BLACK_KNIGHT_HEURISTICS,
+        BLACK_BISHOP_HEURISTICS,
+        BLACK_ROOK_HEURISTICS,
+        BLACK_QUEEN_HEURISTICS,
+        BLACK_KING_HEURISTICS,
+    ]; 4];
+    let mut phases: usize = 0;
+    while phases < 4 {
+        if phases & WHITE_KING_ENDGAME != 0 {
+            tables[phases][5] = ENDGAME_WHITE_KING_HEURISTICS;
+        }
+        if phases & BLACK_KING_ENDGAME != 0 {
+            tables[phases][11] = ENDGAME_BLACK_KING_HEURISTICS;
+        }
+        phases += 1;
+    }
+    tables
+};
 
 pub const ENDGAME_WHITE_KING_HEURISTICS: [i32; 64] = [
     -50, -40, -30, -30, -30, -30, -40, -50, // Rank 1
@@ -1,5 +1,7 @@
 use crate::board_geometry_templates::*;
-use crate::{board::Board, gamestate::GameState};
+use crate::{board::Board, gamestate::GameState, uci::error::UciError};
+
+pub const STARTING_POSITION_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
 fn index_to_chess_notation(idx: u8) -> String {
     let coords: (u8, u8) = (idx / 8, idx % 8);
@@ -94,29 +96,28 @@ pub fn board_to_fen(board: &Board, state: &GameState, whose_move: &u8) -> String
         _ => " b ",
     });
 
-    let castling_rights: [bool; 4] = match state.castling_rights {
-        3 => [true, true, false, false],
-        7 => [true, true, true, false],
-        11 => [true, true, false, true],
-        12 => [false, false, true, true],
-        13 => [true, false, true, true],
-        14 => [false, true, true, true],
-        15 => [true; 4],
-        _ => [false; 4],
-    };
+    // standard rooks are written as KQkq, any other rook by its file like in Shredder-FEN
     let mut castling_str: String = String::new();
-
-    if castling_rights[0] {
-        castling_str.push('K');
-    }
-    if castling_rights[1] {
-        castling_str.push('Q');
-    }
-    if castling_rights[2] {
-        castling_str.push('k');
-    }
-    if castling_rights[3] {
-        castling_str.push('q');
+    for (i, standard_letter) in ['K', 'Q', 'k', 'q'].into_iter().enumerate() {
+        if state.castling_rights & (1 << i) == 0 {
+            continue;
+        }
+        let rook_square: u8 = state.castling_rooks[i];
+        let king_square: u8 = if i < 2 {
+            board.white_king_square
+        } else {
+            board.black_king_square
+        };
+        if rook_square == STANDARD_CASTLING_ROOKS[i] && king_square == (rook_square & 56) + 4 {
+            castling_str.push(standard_letter);
+        } else {
+            let file: char = (b'a' + rook_square % 8) as char;
+            castling_str.push(if i < 2 {
+                file.to_ascii_uppercase()
+            } else {
+                file
+            });
+        }
     }
 
     fen.push_str(if castling_str.is_empty() {
@@ -139,7 +140,13 @@ pub fn board_to_fen(board: &Board, state: &GameState, whose_move: &u8) -> String
     return fen;
 }
 
+// for FENs known to be valid, e.g. the ones written in the code
 pub fn fen_to_board(fen: &str) -> (Board, GameState) {
+    return parse_fen(fen).unwrap_or_else(|e| panic!("{e}"));
+}
+
+pub fn parse_fen(fen: &str) -> Result<(Board, GameState), UciError> {
+    let invalid = |reason: &str| UciError::InvalidFen(format!("{reason} in \"{fen}\""));
     let mut board: Board = Board {
         bitboards: [0; 12],
         occupancies: [0; 2],
@@ -147,10 +154,12 @@ pub fn fen_to_board(fen: &str) -> (Board, GameState) {
         cached_pieces: [0; 64],
         white_king_square: 0,
         black_king_square: 0,
+        king_phases: 0,
     };
     let mut state: GameState = GameState {
         en_passant_target: None,
         castling_rights: 0,
+        castling_rooks: STANDARD_CASTLING_ROOKS,
         fifty_moves_rule_counter: 0,
         moves_history: Vec::new(),
         total_moves_amount: 0,
@@ -158,88 +167,152 @@ pub fn fen_to_board(fen: &str) -> (Board, GameState) {
         irreversible_moves: Vec::new(),
         check_squares: [0; 5],
     };
-    let mut index: u8 = 63;
     let mut split_fen: std::str::SplitWhitespace<'_> = fen.split_whitespace();
-    let (pieces, color, castling, en_passant, fifty_moves, total_moves): (
-        &str,
-        &str,
-        &str,
-        &str,
-        &str,
-        &str,
-    ) = (
-        split_fen.next().unwrap(),
-        split_fen.next().unwrap(),
-        split_fen.next().unwrap(),
-        split_fen.next().unwrap(),
-        split_fen.next().unwrap(),
-        split_fen.next().unwrap(),
+    let pieces: &str = split_fen.next().ok_or_else(|| invalid("no pieces"))?;
+    let color: &str = split_fen.next().ok_or_else(|| invalid("no side to move"))?;
+    let castling: &str = split_fen
+        .next()
+        .ok_or_else(|| invalid("no castling rights"))?;
+    let en_passant: &str = split_fen
+        .next()
+        .ok_or_else(|| invalid("no en passant square"))?;
+    // the move counters are often left out
+    let (fifty_moves, total_moves): (&str, &str) = (
+        split_fen.next().unwrap_or("0"),
+        split_fen.next().unwrap_or("1"),
     );
 
-    for c in pieces.chars() {
-        if c.is_ascii_digit() {
-            let empty_squares: u8 = c.to_digit(10).unwrap() as u8;
-            if index < empty_squares {
-                break;
+    let ranks: Vec<&str> = pieces.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(invalid("expected 8 ranks"));
+    }
+    for (i, rank_pieces) in ranks.iter().enumerate() {
+        let rank: usize = 7 - i;
+        let mut file: usize = 0;
+        for c in rank_pieces.chars() {
+            if let '1'..='8' = c {
+                file += c.to_digit(10).unwrap() as usize;
+                continue;
             }
-            index -= empty_squares;
-        } else if c.is_ascii_alphabetic() {
-            let mirrored_index: u8 = ((index / 8) * 8 + (8 - (index % 8))) - 1;
-            match c {
-                'P' => board.bitboards[0] |= 1 << mirrored_index,
-                'p' => board.bitboards[6] |= 1 << mirrored_index,
-                'B' => board.bitboards[2] |= 1 << mirrored_index,
-                'b' => board.bitboards[8] |= 1 << mirrored_index,
-                'N' => board.bitboards[1] |= 1 << mirrored_index,
-                'n' => board.bitboards[7] |= 1 << mirrored_index,
-                'R' => board.bitboards[3] |= 1 << mirrored_index,
-                'r' => board.bitboards[9] |= 1 << mirrored_index,
-                'Q' => board.bitboards[4] |= 1 << mirrored_index,
-                'q' => board.bitboards[10] |= 1 << mirrored_index,
-                'K' => board.bitboards[5] |= 1 << mirrored_index,
-                'k' => board.bitboards[11] |= 1 << mirrored_index,
-                _ => unreachable!(),
+            let bitboard_index: usize = match c {
+                'P' => 0,
+                'N' => 1,
+                'B' => 2,
+                'R' => 3,
+                'Q' => 4,
+                'K' => 5,
+                'p' => 6,
+                'n' => 7,
+                'b' => 8,
+                'r' => 9,
+                'q' => 10,
+                'k' => 11,
+                _ => return Err(invalid(&format!("unexpected character {c}"))),
             };
-            if index == 0 {
-                break;
+            if file >= 8 {
+                return Err(invalid(&format!("rank {} is not 8 squares long", rank + 1)));
             }
-            index -= 1;
-        } else if c == '/' {
-            continue;
+            board.bitboards[bitboard_index] |= 1 << (rank * 8 + file);
+            file += 1;
+        }
+        if file != 8 {
+            return Err(invalid(&format!("rank {} is not 8 squares long", rank + 1)));
         }
     }
+    if board.bitboards[5].count_ones() != 1 || board.bitboards[11].count_ones() != 1 {
+        return Err(invalid("each side needs exactly one king"));
+    }
+    if (board.bitboards[0] | board.bitboards[6]) & (RANK_1 | RANK_8) != 0 {
+        return Err(invalid("pawns on the first or the last rank"));
+    }
+    board.total_occupancy();
+    board.update_full_cache();
 
     state.whose_turn = match color {
         "w" => 8,
         "b" => 16,
-        _ => unreachable!(),
+        _ => return Err(invalid(&format!("unexpected side to move {color}"))),
     };
 
-    for right in castling.chars() {
-        match right {
-            'Q' => state.castling_rights |= WHITE_LONG,
-            'q' => state.castling_rights |= BLACK_LONG,
-            'K' => state.castling_rights |= WHITE_SHORT,
-            'k' => state.castling_rights |= BLACK_SHORT,
-            '-' => (),
-            _ => unreachable!(),
-        };
-    }
-    match en_passant {
-        // wrong logic, because en passant should be capturable
-        "-" => (),
-        c_n => {
-            state.en_passant_target = Some(chess_notation_to_index(c_n));
+    // the target is behind an enemy pawn that has just moved two squares,
+    // so both the target and the square the pawn came from are empty
+    match (en_passant.as_bytes(), state.whose_turn) {
+        (b"-", _) => (),
+        ([b'a'..=b'h', b'6'], 8) | ([b'a'..=b'h', b'3'], 16) => {
+            let target: u8 = chess_notation_to_index(en_passant);
+            let (enemy_pawn, pawn_square, origin_square): (usize, u8, u8) = if state.whose_turn == 8
+            {
+                (6, target - 8, target + 8)
+            } else {
+                (0, target + 8, target - 8)
+            };
+            if board.bitboards[enemy_pawn] & (1 << pawn_square) == 0 {
+                return Err(invalid(&format!(
+                    "no pawn in front of the en passant square {en_passant}"
+                )));
+            }
+            if board.total_occupancy & ((1 << target) | (1 << origin_square)) != 0 {
+                return Err(invalid(&format!(
+                    "the en passant square {en_passant} or the one behind it is occupied"
+                )));
+            }
+            state.en_passant_target = Some(target);
+        }
+        _ => {
+            return Err(invalid(&format!(
+                "unexpected en passant square {en_passant}"
+            )));
         }
     }
 
-    (state.fifty_moves_rule_counter, state.total_moves_amount) =
-        (fifty_moves.parse().unwrap(), total_moves.parse().unwrap());
+    state.fifty_moves_rule_counter = fifty_moves
+        .parse()
+        .map_err(|_| invalid(&format!("unexpected halfmove clock {fifty_moves}")))?;
+    // the counter is too small for very long games, they are not rejected for it
+    state.total_moves_amount = total_moves
+        .parse::<u32>()
+        .map_err(|_| invalid(&format!("unexpected move number {total_moves}")))?
+        .min(u8::MAX as u32) as u8;
 
-    board.total_occupancy();
-    board.update_full_cache();
     board.white_king_square = board.bitboards[5].trailing_zeros() as u8;
     board.black_king_square = board.bitboards[11].trailing_zeros() as u8;
 
-    return (board, state);
+    // KQkq (X-FEN) picks the outermost rook on that side of the king, while the file
+    // letters (Shredder-FEN) name the rook directly, which chess960 needs
+    for right in castling.chars() {
+        let (king_square, rook, rank_start, rights_offset): (u8, u16, u8, usize) = match right {
+            '-' => continue,
+            'K' | 'Q' | 'A'..='H' => (board.white_king_square, WHITE_ROOK_U16, 0, 0),
+            'k' | 'q' | 'a'..='h' => (board.black_king_square, BLACK_ROOK_U16, 56, 2),
+            _ => return Err(invalid(&format!("unexpected castling right {right}"))),
+        };
+        if king_square & 56 != rank_start {
+            return Err(invalid(&format!(
+                "castling right {right} without the king on its first rank"
+            )));
+        }
+        let is_rook = |square: &u8| board.cached_pieces[*square as usize] == rook;
+        let rook_square: Option<u8> = match right.to_ascii_lowercase() {
+            'k' => (king_square + 1..rank_start + 8).rev().find(is_rook),
+            'q' => (rank_start..king_square).find(is_rook),
+            file => Some(rank_start + (file as u8 - b'a')).filter(is_rook),
+        };
+        let Some(rook_square) = rook_square else {
+            return Err(invalid(&format!("no rook for the castling right {right}")));
+        };
+        let right_index: usize = rights_offset + if rook_square > king_square { 0 } else { 1 };
+        state.castling_rights |= 1 << right_index;
+        state.castling_rooks[right_index] = rook_square;
+    }
+
+    let waiting_king_square: u8 = if state.whose_turn == 8 {
+        board.black_king_square
+    } else {
+        board.white_king_square
+    };
+    if board.is_square_attacked(waiting_king_square, state.whose_turn) {
+        return Err(invalid("the side not to move is in check"));
+    }
+
+    return Ok((board, state));
 }
@@ -1,3 +1,4 @@
 pub mod fen_converter;
+pub mod move_converter;
 #[cfg(feature = "pgn-converter")]
 pub mod pgn_converter;
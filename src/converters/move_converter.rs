@@ -0,0 +1,118 @@
+use crate::{
+    board::Board,
+    board_geometry_templates::*,
+    constants::attacks::{COORDS_TO_INDICES, INDICES_TO_COORDS},
+    gamestate::GameState,
+    moves::MoveList,
+    uci::error::UciError,
+};
+use std::collections::HashMap;
+
+// converts a long algebraic move (e.g. "e2e4", "e7e8q") into the u16 move encoding.
+// the flag is restored from the position: castling, en passant and promotions.
+// only a legal move of the side to move is accepted
+pub fn uci_to_move(board: &Board, state: &GameState, uci_move: &str) -> Result<u16, UciError> {
+    let illegal = || UciError::IllegalMove(uci_move.to_string());
+    if uci_move.len() != 4 && uci_move.len() != 5 {
+        return Err(illegal());
+    }
+    let coords_to_indices: &HashMap<String, u8> = &COORDS_TO_INDICES;
+    let from: u16 = *uci_move
+        .get(0..2)
+        .and_then(|c| coords_to_indices.get(c))
+        .ok_or_else(illegal)? as u16;
+    let mut to: u16 = *uci_move
+        .get(2..4)
+        .and_then(|c| coords_to_indices.get(c))
+        .ok_or_else(illegal)? as u16;
+    let moving_piece: u16 = board.piece_at(from);
+    if moving_piece == 0 {
+        return Err(illegal());
+    }
+    let is_king: bool = moving_piece == WHITE_KING_U16 || moving_piece == BLACK_KING_U16;
+    let own_rook: u16 = if moving_piece == WHITE_KING_U16 {
+        WHITE_ROOK_U16
+    } else {
+        BLACK_ROOK_U16
+    };
+    // castling comes either as the king taking its own rook (chess960) or as the
+    // king moving two squares (standard), which is turned into the former
+    let is_castling: bool = is_king && (board.piece_at(to) == own_rook || from.abs_diff(to) == 2);
+    if is_castling && board.piece_at(to) != own_rook {
+        let rights_offset: usize = if moving_piece == WHITE_KING_U16 { 0 } else { 2 };
+        let right_index: usize = rights_offset + if to > from { 0 } else { 1 };
+        to = state.castling_rooks[right_index] as u16;
+    }
+
+    let flag: u16 = match uci_move.get(4..5) {
+        Some("n") => 3,
+        Some("b") => 4,
+        Some("r") => 5,
+        Some("q") => 6,
+        Some(_) => return Err(illegal()),
+        None => {
+            if is_castling {
+                1
+            } else if (moving_piece == WHITE_PAWN_U16 || moving_piece == BLACK_PAWN_U16)
+                && state.en_passant_target == Some(to as u8)
+                && from % 8 != to % 8
+            {
+                2
+            } else {
+                0
+            }
+        }
+    };
+    let m: u16 = from | (to << TO_SHIFT) | (flag << MARK_SHIFT);
+
+    let color: u16 = state.whose_turn;
+    let mut moves: MoveList = MoveList {
+        pseudo_moves: [0; 192],
+        first_not_occupied: 0,
+    };
+    board.pawn_moves(state, color, &mut moves, false);
+    board.knight_moves(color, &mut moves, state, false);
+    board.bishop_moves(color, &mut moves, state, false);
+    board.queen_moves(color, &mut moves, state, false);
+    board.rook_moves(color, &mut moves, state, false);
+    board.king_moves(state, color, &mut moves, false);
+    let generated_move: u16 = *moves.pseudo_moves[..moves.first_not_occupied]
+        .iter()
+        .find(|generated| without_check_flag(**generated) == m)
+        .ok_or_else(illegal)?;
+
+    let (mut board_after, mut state_after): (Board, GameState) = (board.clone(), state.clone());
+    board_after.perform_move(generated_move, &mut state_after, color, &mut 0, &mut 0);
+    let (king_square, enemy_color): (u8, u16) = if color == 8 {
+        (board_after.white_king_square, 16)
+    } else {
+        (board_after.black_king_square, 8)
+    };
+    if board_after.is_square_attacked(king_square, enemy_color) {
+        return Err(illegal());
+    }
+    return Ok(m);
+}
+
+// castling is written as the king taking its rook in chess960, and as the
+// king's two square move in standard chess
+pub fn move_to_uci(m: u16, chess960: bool) -> String {
+    let indices_to_coords: &HashMap<u8, String> = &INDICES_TO_COORDS;
+    let to: u8 = if is_castling(m) && !chess960 {
+        castling_destinations(from_square(m), to_square(m) as u8).0
+    } else {
+        to_square(m) as u8
+    };
+    return format!(
+        "{}{}{}",
+        indices_to_coords.get(&from_square(m)).unwrap(),
+        indices_to_coords.get(&to).unwrap(),
+        match (m & MARK_MASK) >> MARK_SHIFT {
+            3 | 10 => "n",
+            4 | 11 => "b",
+            5 | 12 => "r",
+            6 | 13 => "q",
+            _ => "",
+        },
+    );
+}
@@ -1,47 +1,53 @@
 use serde_json::from_str;
-use std::collections::HashMap;
+use std::{collections::HashMap, fs::read_to_string};
 
 pub fn load_opening_book() -> HashMap<u64, [Option<u16>; 5]> {
     return from_str(OPENING_BOOK_JSON.trim()).unwrap();
 }
 
+// a book in the same format as the one produced by the opening generator
+pub fn load_opening_book_file(path: &str) -> Result<HashMap<u64, [Option<u16>; 5]>, String> {
+    let book_json: String = read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+    return from_str(book_json.trim()).map_err(|e| format!("cannot parse {path}: {e}"));
+}
+
 pub const OPENING_BOOK_JSON: &'static str = r#"{
-  "18141218203946229002": [
+  "4711715386447084131": [
     2868,
     2942,
     2356,
     2745,
     2291
   ],
-  "16530221842145309579": [
+  "6901424300633511138": [
     1153,
     1804,
     1350,
     1292,
     1869
   ],
-  "6032843808370189549": [
+  "16796485438480761732": [
     2942,
     2745,
     2803,
     2868,
     2291
   ],
-  "16131203171172236747": [
+  "7312832218900667042": [
     2291,
     2745,
     2356,
     2226,
     2942
   ],
-  "2419184972589062927": [
+  "11183359349220801638": [
     2942,
     2745,
     2291,
     2617,
     2868
   ],
-  "2077772785297693683": [
+  "11994307020491570330": [
     2868,
     2942,
     2673,
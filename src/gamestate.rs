@@ -1,18 +1,22 @@
 use crate::{
     board::Board,
-    board_geometry_templates::{BLACK_LONG, BLACK_SHORT, WHITE_LONG, WHITE_SHORT},
-    constants::attacks::*,
+    board_geometry_templates::{BLACK_ROOK_U16, STANDARD_CASTLING_ROOKS, WHITE_ROOK_U16},
+    constants::{
+        attacks::*,
+        zobrist_hashes::{CASTLING_HASHES, EN_PASSANT_HASHES},
+    },
 };
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct GameState {
     pub en_passant_target: Option<u8>, // the square BEHIND the pawn that has moved two squares
     pub castling_rights: u8,
+    pub castling_rooks: [u8; 4], // starting squares of the rooks, in the order of the castling rights bits
     pub fifty_moves_rule_counter: u8, // how many moves since the last capture/pawn advancement. enforces 50-move rule
     pub moves_history: Vec<PreviousMove>,
     pub total_moves_amount: u8,
     pub whose_turn: u16,
-    pub irreversible_moves: Vec<u64>,
+    pub irreversible_moves: Vec<u64>, // hashes of the positions before each move, the last fifty_moves_rule_counter of them can repeat
     pub check_squares: [u64; 5],
 }
 
@@ -52,18 +56,29 @@ pub struct PreviousMove {
     pub previous_castling_rights: u8,
     pub material_difference: i32,
     pub check_squares: [u64; 5],
+    pub previous_fifty_moves_rule_counter: u8,
 }
 
 impl GameState {
     pub fn new(board: &Board) -> Self {
+        // standard castling rights for the kings and rooks still on their initial squares
+        let mut castling_rights: u8 = 0;
+        for (i, rook_square) in STANDARD_CASTLING_ROOKS.iter().enumerate() {
+            let (king_square, rook): (u8, u16) = if i < 2 {
+                (board.white_king_square, WHITE_ROOK_U16)
+            } else {
+                (board.black_king_square, BLACK_ROOK_U16)
+            };
+            if king_square == (rook_square & 56) + 4
+                && board.cached_pieces[*rook_square as usize] == rook
+            {
+                castling_rights |= 1 << i;
+            }
+        }
         return Self {
             en_passant_target: None,
-            castling_rights: match (board.white_king_square, board.black_king_square) {
-                (4, 60) => 15,
-                (4, _) => WHITE_SHORT | WHITE_LONG,
-                (_, 60) => BLACK_SHORT | BLACK_LONG,
-                (_, _) => 0,
-            },
+            castling_rights,
+            castling_rooks: STANDARD_CASTLING_ROOKS,
             fifty_moves_rule_counter: 1,
             moves_history: Vec::with_capacity(50),
             total_moves_amount: 0,
@@ -73,17 +88,44 @@ impl GameState {
         };
     }
 
-    pub fn is_repetition(&self, current_hash: u64) -> bool {
-        let mut repetition_counter: u8 = 1;
-        for pos in &self.irreversible_moves {
-            if *pos == current_hash {
-                repetition_counter += 1;
+    // the castling right that is lost when a rook leaves or gets captured on `square`
+    #[inline(always)]
+    pub fn castling_right_of_rook(&self, square: u8) -> u8 {
+        let mut right: u8 = 0;
+        for i in 0..4 {
+            if self.castling_rooks[i] == square {
+                right |= 1 << i;
             }
         }
-        if repetition_counter > 1 {
-            return true;
+        return right;
+    }
+
+    // the part of the zobrist hash that is not about the pieces or the side to move
+    #[inline(always)]
+    pub fn castling_and_en_passant_hash(&self) -> u64 {
+        let mut hash: u64 = CASTLING_HASHES[self.castling_rights as usize];
+        if let Some(square) = self.en_passant_target {
+            hash ^= EN_PASSANT_HASHES[(square % 8) as usize];
         }
-        return false;
+        return hash;
+    }
+
+    // only the positions since the last capture or pawn move can repeat, and only every
+    // second one of them has the same side to move
+    pub fn is_repetition(&self, current_hash: u64) -> bool {
+        let history_len: usize = self.irreversible_moves.len();
+        let reversible: usize = (self.fifty_moves_rule_counter as usize).min(history_len);
+        return self.irreversible_moves[history_len - reversible..]
+            .iter()
+            .rev()
+            .skip(1)
+            .step_by(2)
+            .any(|pos| *pos == current_hash);
+    }
+
+    #[inline(always)]
+    pub fn is_draw_by_rule(&self, current_hash: u64) -> bool {
+        return self.fifty_moves_rule_counter >= 100 || self.is_repetition(current_hash);
     }
 
     #[inline(always)]
@@ -1,18 +1,15 @@
+use crate::search::Engine;
 #[cfg(not(any(feature = "opening-book", feature = "debug-ui")))]
 use crate::uci::uci_output;
+#[cfg(not(any(feature = "opening-book", feature = "debug-ui")))]
+use crate::{bench::run_bench, search::SEARCH_THREAD_STACK_SIZE};
 #[cfg(feature = "debug-ui")]
 use crate::{
     board_geometry_templates::{MARK_SHIFT, TO_SHIFT},
     converters::fen_converter::fen_to_board,
 };
-use crate::{
-    constants::attacks::{
-        compute_all_lines, compute_all_rays, compute_all_rays_from,
-        initialize_sliding_attack_tables,
-    },
-    search::Engine,
-};
 
+pub mod bench;
 pub mod board;
 pub mod board_geometry_templates;
 pub mod constants;
@@ -26,24 +23,31 @@ mod opening_generator;
 pub mod pawn_structure;
 pub mod search;
 pub mod tests;
+pub mod time_management;
 pub mod transposition;
 pub mod tuning;
 pub mod uci;
 
 #[cfg(not(any(feature = "opening-book", feature = "debug-ui")))]
 fn main() -> () {
-    /* initialize_sliding_attack_tables(), compute_all_rays(),
-    compute_all_lines,
-    and compute_all_rays_from() have to be called
-    in the beginning of program and tests */
-
-    initialize_sliding_attack_tables();
-    compute_all_rays();
-    compute_all_rays_from();
-    compute_all_lines();
-
     let mut engine: Engine = Engine::new(8, 20);
 
+    // "Ferrous bench [depth]" searches the bench suite and exits
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("bench") {
+        let depth: u8 = args
+            .get(2)
+            .and_then(|d| d.parse::<u8>().ok())
+            .unwrap_or(bench::BENCH_DEPTH);
+        std::thread::Builder::new()
+            .stack_size(SEARCH_THREAD_STACK_SIZE)
+            .spawn(move || run_bench(&mut engine, depth))
+            .unwrap()
+            .join()
+            .unwrap();
+        std::process::exit(0);
+    }
+
     uci_output(&mut engine);
 
     std::process::exit(0);
@@ -62,11 +66,6 @@ async fn main() {
     println!("hello, debug-ui");
     set_fullscreen(true);
 
-    initialize_sliding_attack_tables();
-    compute_all_rays();
-    compute_all_rays_from();
-    compute_all_lines();
-    compute_mvvlva();
     let pieces_images: [Texture2D; 12] = [
         load_texture("pieces/white_pawn.png").await.unwrap(),
         load_texture("pieces/white_knight.png").await.unwrap(),
@@ -130,12 +129,6 @@ async fn main() {
 fn main() -> () {
     use std::collections::HashMap;
 
-    initialize_sliding_attack_tables();
-    compute_all_rays();
-    compute_all_rays_from();
-    compute_all_lines();
-    compute_mvvlva();
-
     let mut opening_engine: Engine = Engine::new(8, 11);
     opening_engine.fill_opening_book_iterative(&mut HashMap::new());
 }
@@ -5,7 +5,6 @@ use crate::{
     board_geometry_templates::*,
     constants::{
         attacks::EN_PASSANT_TARGETS,
-        heuristics::*,
         masks::BIT_MASKS,
         piece_values::*,
         zobrist_hashes::{BLACK_ZOBRIST_KEY, WHITE_ZOBRIST_KEY, ZOBRIST_HASH_TABLE},
@@ -28,7 +27,7 @@ impl Board {
         color: u16,
     ) -> () {
         *evaluation -= VALUE_TABLE[captured_table_idx];
-        let dest_heuristic: i32 = unsafe { HEURISTICS_TABLE[captured_table_idx][to_sq] };
+        let dest_heuristic: i32 = self.heuristics()[captured_table_idx][to_sq];
         *evaluation -= if color == 8 {
             -dest_heuristic
         } else {
@@ -36,86 +35,158 @@ impl Board {
         };
         previous_move.captured_piece |= enemy;
         let capture: u64 = !BIT_MASKS[to_sq];
-        if enemy == WHITE_ROOK_U16 {
-            if to_sq == 7 {
-                state.castling_rights &= !WHITE_SHORT;
-            } else if to_sq == 0 {
-                state.castling_rights &= !WHITE_LONG;
-            }
-        } else if enemy == BLACK_ROOK_U16 {
-            if to_sq == 63 {
-                state.castling_rights &= !BLACK_SHORT;
-            } else if to_sq == 56 {
-                state.castling_rights &= !BLACK_LONG;
-            }
+        if enemy == WHITE_ROOK_U16 || enemy == BLACK_ROOK_U16 {
+            state.castling_rights &= !state.castling_right_of_rook(to_sq as u8);
         }
         self.bitboards[captured_table_idx] &= capture;
         self.occupancies[occupancy_idx] &= capture;
         *current_hash ^= ZOBRIST_HASH_TABLE[captured_table_idx * 64 + to_sq];
     }
 
+    // the king moves onto its own rook's square in the encoding, so castling
+    // does not go through the usual path of a move or a capture
     #[cold]
     fn castling(
         &mut self,
-        previous_move: &mut PreviousMove,
-        from_sq: usize,
-        to_sq: usize,
+        piece_move: u16,
+        state: &mut GameState,
         color: u16,
-        eval: &mut i32,
+        evaluation: &mut i32,
         current_hash: &mut u64,
     ) -> () {
-        let (
-            rook,
-            rook_from,
-            rook_to,
-            occupancy,
-            (rook_from_heuristic, rook_to_heuristic),
-            rook_bb,
-        ) = if color == 8 {
-            let (rook_from_idx, rook_to_idx) = if to_sq > from_sq { (7, 5) } else { (0, 3) };
-            (
-                WHITE_ROOK_U16,
-                rook_from_idx,
-                rook_to_idx,
-                &mut self.occupancies[0],
-                (
-                    WHITE_ROOK_HEURISTICS[rook_from_idx],
-                    WHITE_ROOK_HEURISTICS[rook_to_idx],
-                ),
-                &mut self.bitboards[3],
-            )
+        let (king_from, rook_from): (usize, usize) = (
+            from_square(piece_move) as usize,
+            to_square(piece_move) as usize,
+        );
+        let (king_to, rook_to): (u8, u8) = castling_destinations(king_from as u8, rook_from as u8);
+        let (king_to, rook_to): (usize, usize) = (king_to as usize, rook_to as usize);
+        let (king, rook, occupancy_idx): (u16, u16, usize) = if color == 8 {
+            (WHITE_KING_U16, WHITE_ROOK_U16, 0)
         } else {
-            let (rook_from_idx, rook_to_idx) = if to_sq > from_sq { (63, 61) } else { (56, 59) };
-            (
-                BLACK_ROOK_U16,
-                rook_from_idx,
-                rook_to_idx,
-                &mut self.occupancies[1],
-                (
-                    BLACK_ROOK_HEURISTICS[rook_from_idx],
-                    BLACK_ROOK_HEURISTICS[rook_to_idx],
-                ),
-                &mut self.bitboards[9],
-            )
+            (BLACK_KING_U16, BLACK_ROOK_U16, 1)
+        };
+        let evaluation_before: i32 = *evaluation;
+        let mut previous_move: PreviousMove = PreviousMove {
+            moved_piece: piece_move,
+            captured_piece: 0,
+            previous_en_passant: state.en_passant_target,
+            previous_castling_rights: state.castling_rights,
+            material_difference: 0,
+            move_flag: (piece_move & MARK_MASK) >> MARK_SHIFT,
+            check_squares: state.check_squares.clone(),
+            previous_fifty_moves_rule_counter: state.fifty_moves_rule_counter,
+        };
+        state.fifty_moves_rule_counter = state.fifty_moves_rule_counter.saturating_add(1);
+
+        self.relocate_castling_pieces(
+            (king, king_from, king_to),
+            (rook, rook_from, rook_to),
+            occupancy_idx,
+            evaluation,
+            current_hash,
+        );
+        if color == 8 {
+            self.white_king_square = king_to as u8;
+            state.castling_rights &= NOT_WHITE_CASTLING;
+        } else {
+            self.black_king_square = king_to as u8;
+            state.castling_rights &= NOT_BLACK_CASTLING;
+        }
+        state.en_passant_target = None;
+        previous_move.material_difference = *evaluation - evaluation_before;
+
+        let enemy_king: u8 = if color == 8 {
+            self.white_king_square
+        } else {
+            self.black_king_square
         };
+        state.calculate_check_squares(enemy_king as usize, self.total_occupancy, color);
+        *current_hash ^= state.castling_and_en_passant_hash();
+        state.moves_history.push(previous_move);
+    }
 
-        self.cached_pieces.swap(rook_from, rook_to);
-        previous_move.move_flag = 0b0001;
+    #[cold]
+    fn cancel_castling(
+        &mut self,
+        previous_move: &PreviousMove,
+        state: &mut GameState,
+        color: u16,
+        current_hash: &mut u64,
+    ) -> () {
+        let m: u16 = previous_move.moved_piece;
+        let (king_from, rook_from): (usize, usize) =
+            (from_square(m) as usize, to_square(m) as usize);
+        let (king_to, rook_to): (u8, u8) = castling_destinations(king_from as u8, rook_from as u8);
+        let (king_to, rook_to): (usize, usize) = (king_to as usize, rook_to as usize);
+        let (king, rook, occupancy_idx): (u16, u16, usize) = if color == 8 {
+            (WHITE_KING_U16, WHITE_ROOK_U16, 0)
+        } else {
+            (BLACK_KING_U16, BLACK_ROOK_U16, 1)
+        };
 
-        let (start, end): (u64, u64) = (!BIT_MASKS[rook_from], BIT_MASKS[rook_to]);
-        self.total_occupancy = (self.total_occupancy & start) | end;
+        // the evaluation is restored by the caller from the material difference
+        self.relocate_castling_pieces(
+            (king, king_to, king_from),
+            (rook, rook_to, rook_from),
+            occupancy_idx,
+            &mut 0,
+            current_hash,
+        );
+        if color == 8 {
+            self.white_king_square = king_from as u8;
+        } else {
+            self.black_king_square = king_from as u8;
+        }
+        state.castling_rights = previous_move.previous_castling_rights;
+        state.en_passant_target = previous_move.previous_en_passant;
+        state.check_squares = previous_move.check_squares;
+        *current_hash ^= state.castling_and_en_passant_hash();
+    }
 
-        *occupancy = (*occupancy & start) | end;
-        *rook_bb = (*rook_bb & start) | end;
-        *eval += if color == 8 {
-            rook_to_heuristic - rook_from_heuristic
+    // (piece, from, to) of the king and the rook. both pieces are lifted before
+    // they are put down, as the squares may overlap in chess960
+    #[inline(always)]
+    fn relocate_castling_pieces(
+        &mut self,
+        (king, king_from, king_to): (u16, usize, usize),
+        (rook, rook_from, rook_to): (u16, usize, usize),
+        occupancy_idx: usize,
+        evaluation: &mut i32,
+        current_hash: &mut u64,
+    ) -> () {
+        let (king_idx, rook_idx): (usize, usize) = (king as usize - 1, rook as usize - 1);
+        let lifted: u64 = BIT_MASKS[king_from] | BIT_MASKS[rook_from];
+        let placed: u64 = BIT_MASKS[king_to] | BIT_MASKS[rook_to];
+
+        self.bitboards[king_idx] =
+            (self.bitboards[king_idx] & !BIT_MASKS[king_from]) | BIT_MASKS[king_to];
+        self.bitboards[rook_idx] =
+            (self.bitboards[rook_idx] & !BIT_MASKS[rook_from]) | BIT_MASKS[rook_to];
+        self.occupancies[occupancy_idx] = (self.occupancies[occupancy_idx] & !lifted) | placed;
+        self.total_occupancy = (self.total_occupancy & !lifted) | placed;
+
+        self.cached_pieces[king_from] = 0;
+        self.cached_pieces[rook_from] = 0;
+        self.cached_pieces[king_to] = king;
+        self.cached_pieces[rook_to] = rook;
+
+        let piece_heuristics: &[[i32; 64]; 12] = self.heuristics();
+        let positional_gain: i32 = piece_heuristics[king_idx][king_to]
+            - piece_heuristics[king_idx][king_from]
+            + piece_heuristics[rook_idx][rook_to]
+            - piece_heuristics[rook_idx][rook_from];
+        *evaluation += if king == WHITE_KING_U16 {
+            positional_gain
         } else {
-            rook_from_heuristic - rook_to_heuristic
+            -positional_gain
         };
 
-        let rook_hash: usize = (rook as usize - 1) * 64;
-        *current_hash ^= ZOBRIST_HASH_TABLE[rook_hash + rook_from];
-        *current_hash ^= ZOBRIST_HASH_TABLE[rook_hash + rook_to];
+        *current_hash ^= WHITE_ZOBRIST_KEY;
+        *current_hash ^= BLACK_ZOBRIST_KEY;
+        *current_hash ^= ZOBRIST_HASH_TABLE[king_idx * 64 + king_from];
+        *current_hash ^= ZOBRIST_HASH_TABLE[king_idx * 64 + king_to];
+        *current_hash ^= ZOBRIST_HASH_TABLE[rook_idx * 64 + rook_from];
+        *current_hash ^= ZOBRIST_HASH_TABLE[rook_idx * 64 + rook_to];
     }
 
     #[cold]
@@ -127,6 +198,7 @@ impl Board {
         current_hash: &mut u64,
     ) -> () {
         let e_p: u8 = state.en_passant_target.unwrap();
+        let piece_heuristics: &[[i32; 64]; 12] = self.heuristics();
         let (pawns, occupancy, captured_pawn_square, mut material_subtraction) = match color {
             8 => (
                 &mut self.bitboards[6],
@@ -141,11 +213,10 @@ impl Board {
                 -PAWN_VALUE,
             ),
         };
-        let piece_heuristics: *const [[i32; 64]; 12] = &raw const HEURISTICS_TABLE;
         material_subtraction -= if color == 8 {
-            unsafe { -(*piece_heuristics)[6][captured_pawn_square] }
+            -piece_heuristics[6][captured_pawn_square]
         } else {
-            unsafe { (*piece_heuristics)[0][captured_pawn_square] }
+            piece_heuristics[0][captured_pawn_square]
         };
         self.cached_pieces[captured_pawn_square] = 0;
         let capture: u64 = !BIT_MASKS[captured_pawn_square];
@@ -194,6 +265,13 @@ impl Board {
         evaluation: &mut i32,
         current_hash: &mut u64,
     ) -> () {
+        state.irreversible_moves.push(*current_hash);
+        // the old castling rights and en passant file leave the hash, the new ones enter it after the move
+        *current_hash ^= state.castling_and_en_passant_hash();
+        if is_castling(piece_move) {
+            self.castling(piece_move, state, color, evaluation, current_hash);
+            return;
+        }
         let evaluation_before: i32 = *evaluation;
 
         let (from_sq, to_sq): (u16, u16) =
@@ -210,8 +288,7 @@ impl Board {
         let (moving_piece_table_idx, occupancy_idx): (usize, usize) =
             get_bb_index(moving_piece, &color);
 
-        let moving_piece_heuristics: &[i32; 64] =
-            unsafe { &HEURISTICS_TABLE[moving_piece_table_idx] };
+        let moving_piece_heuristics: &[i32; 64] = &self.heuristics()[moving_piece_table_idx];
 
         let (from_heuristic, to_heuristic) = (
             moving_piece_heuristics[from_sq_index],
@@ -248,6 +325,16 @@ impl Board {
             material_difference: 0,
             move_flag,
             check_squares: state.check_squares.clone(),
+            previous_fifty_moves_rule_counter: state.fifty_moves_rule_counter,
+        };
+        // captures and pawn moves make every earlier position unreachable
+        state.fifty_moves_rule_counter = if captured_piece != 0
+            || moving_piece == WHITE_PAWN_U16
+            || moving_piece == BLACK_PAWN_U16
+        {
+            0
+        } else {
+            state.fifty_moves_rule_counter.saturating_add(1)
         };
         if captured_piece != 0 {
             let (captured_piece_table_idx, captured_occupancy_idx): (usize, usize) =
@@ -264,16 +351,7 @@ impl Board {
                 color,
             );
         }
-        if move_flag == 1 || move_flag == 8 {
-            self.castling(
-                &mut previous_move,
-                from_sq_index,
-                to_sq_index,
-                color,
-                evaluation,
-                current_hash,
-            );
-        } else if move_flag == 2 || move_flag == 9 {
+        if move_flag == 2 || move_flag == 9 {
             self.en_passant(state, color, evaluation, current_hash);
         }
 
@@ -282,20 +360,7 @@ impl Board {
         cached_pieces[from_sq_index] = 0;
 
         match moving_piece {
-            4 => {
-                if from_sq == 0 {
-                    state.castling_rights &= !WHITE_LONG_MASK;
-                } else if from_sq == 7 {
-                    state.castling_rights &= !WHITE_SHORT_MASK;
-                }
-            }
-            10 => {
-                if from_sq == 56 {
-                    state.castling_rights &= !BLACK_LONG_MASK;
-                } else if from_sq == 63 {
-                    state.castling_rights &= !BLACK_SHORT_MASK;
-                }
-            }
+            4 | 10 => state.castling_rights &= !state.castling_right_of_rook(from_sq as u8),
             6 => {
                 self.white_king_square = to_sq as u8;
                 state.castling_rights &= NOT_WHITE_CASTLING;
@@ -366,10 +431,61 @@ impl Board {
         previous_move.material_difference = *evaluation - evaluation_before;
 
         state.calculate_check_squares(enemy_king as usize, self.total_occupancy, enemy_color);
+        *current_hash ^= state.castling_and_en_passant_hash();
 
         state.moves_history.push(previous_move);
     }
 
+    // passes the turn without moving a piece, for null move pruning. only the side to move
+    // and the en passant square change, the record in the history has an empty move
+    pub fn perform_null_move(
+        &self,
+        state: &mut GameState,
+        color: u16,
+        current_hash: &mut u64,
+    ) -> () {
+        state.irreversible_moves.push(*current_hash);
+        *current_hash ^= state.castling_and_en_passant_hash();
+        state.moves_history.push(PreviousMove {
+            moved_piece: NULL_MOVE,
+            captured_piece: 0,
+            move_flag: 0,
+            previous_en_passant: state.en_passant_target,
+            previous_castling_rights: state.castling_rights,
+            material_difference: 0,
+            check_squares: state.check_squares,
+            previous_fifty_moves_rule_counter: state.fifty_moves_rule_counter,
+        });
+        // positions before a passed turn are not real repetitions
+        state.fifty_moves_rule_counter = 0;
+        state.en_passant_target = None;
+        *current_hash ^= state.castling_and_en_passant_hash();
+        *current_hash ^= WHITE_ZOBRIST_KEY;
+        *current_hash ^= BLACK_ZOBRIST_KEY;
+
+        let own_king: u8 = if color == 8 {
+            self.white_king_square
+        } else {
+            self.black_king_square
+        };
+        state.calculate_check_squares(own_king as usize, self.total_occupancy, color);
+    }
+
+    pub fn cancel_null_move(&self, state: &mut GameState, current_hash: &mut u64) -> () {
+        if let Some(previous_move) = state.moves_history.pop() {
+            debug_assert_eq!(previous_move.moved_piece, NULL_MOVE);
+            state.irreversible_moves.pop();
+            state.fifty_moves_rule_counter = previous_move.previous_fifty_moves_rule_counter;
+            *current_hash ^= state.castling_and_en_passant_hash();
+            state.en_passant_target = previous_move.previous_en_passant;
+            state.castling_rights = previous_move.previous_castling_rights;
+            state.check_squares = previous_move.check_squares;
+            *current_hash ^= state.castling_and_en_passant_hash();
+            *current_hash ^= WHITE_ZOBRIST_KEY;
+            *current_hash ^= BLACK_ZOBRIST_KEY;
+        }
+    }
+
     pub fn cancel_move(
         &mut self,
         state: &mut GameState,
@@ -378,7 +494,14 @@ impl Board {
         current_hash: &mut u64,
     ) -> () {
         if let Some(previous_move) = state.moves_history.pop() {
+            state.irreversible_moves.pop();
+            state.fifty_moves_rule_counter = previous_move.previous_fifty_moves_rule_counter;
             *evaluation -= previous_move.material_difference;
+            *current_hash ^= state.castling_and_en_passant_hash();
+            if is_castling(previous_move.moved_piece) {
+                self.cancel_castling(&previous_move, state, color, current_hash);
+                return;
+            }
 
             let (cached_pieces, zobrist_table) = (&mut self.cached_pieces, &ZOBRIST_HASH_TABLE);
 
@@ -414,12 +537,11 @@ impl Board {
                 _ => (),
             };
 
-            let (promotion, castling, en_passant) = match previous_move.move_flag {
-                0 | 7 => (0, 0, 0),
-                1 | 8 => (0, 1, 0),
-                2 | 9 => (0, 0, 1),
-                3 | 4 | 5 | 6 => (previous_move.move_flag - 2, 0, 0),
-                10 | 11 | 12 | 13 => (previous_move.move_flag - 9, 0, 0),
+            let (promotion, en_passant) = match previous_move.move_flag {
+                0 | 7 => (0, 0),
+                2 | 9 => (0, 1),
+                3 | 4 | 5 | 6 => (previous_move.move_flag - 2, 0),
+                10 | 11 | 12 | 13 => (previous_move.move_flag - 9, 0),
                 _ => unreachable!(),
             };
 
@@ -512,30 +634,10 @@ impl Board {
                 cached_pieces[taken_pawn_square] = pawn;
             }
 
-            if castling != 0 {
-                let (rooks, occupancy, rook_start, rook_end, rook_encoding) = match (start, end) {
-                    (4, 2) => (&mut self.bitboards[3], &mut self.occupancies[0], 0, 3, 4),
-                    (4, 6) => (&mut self.bitboards[3], &mut self.occupancies[0], 7, 5, 4),
-                    (60, 58) => (&mut self.bitboards[9], &mut self.occupancies[1], 56, 59, 10),
-                    (60, 62) => (&mut self.bitboards[9], &mut self.occupancies[1], 63, 61, 10),
-                    _ => unreachable!("from: {start}, to {end}, piece: {main_piece}"),
-                };
-                let (rook_start_bb, rook_end_bb): (u64, u64) =
-                    (BIT_MASKS[rook_start], !BIT_MASKS[rook_end]);
-
-                *rooks = (*rooks & rook_end_bb) | rook_start_bb;
-                *occupancy = (*occupancy & rook_end_bb) | rook_start_bb;
-                self.total_occupancy = (self.total_occupancy & rook_end_bb) | rook_start_bb;
-                cached_pieces.swap(rook_end, rook_start);
-
-                let rook_hash: usize = (rook_encoding - 1) * 64;
-                *current_hash ^= zobrist_table[rook_hash + rook_start];
-                *current_hash ^= zobrist_table[rook_hash + rook_end];
-            }
-
             state.castling_rights = previous_move.previous_castling_rights;
             state.en_passant_target = previous_move.previous_en_passant;
             state.check_squares = previous_move.check_squares;
+            *current_hash ^= state.castling_and_en_passant_hash();
         }
     }
 }
@@ -1,6 +1,7 @@
 use crate::board::Board;
 use crate::board_geometry_templates::*;
 use crate::constants::attacks::*;
+use crate::constants::piece_values::{PAWN_VALUE, VALUE_TABLE};
 use crate::gamestate::GameState;
 
 #[derive(Clone, Copy)]
@@ -25,15 +26,15 @@ impl Board {
         king_square: u8,
     ) -> (bool, u64) {
         let (king_sq, from_sq) = (king_square as usize, from as usize);
-        let king_pawn_line: u64 = unsafe { TWO_SQUARES_LINE[king_sq][from_sq] };
+        let king_pawn_line: u64 = TWO_SQUARES_LINE[king_sq][from_sq];
         if king_pawn_line == 0 {
             return (false, 0);
         }
         let en_passantless_occupancy: u64 = self.total_occupancy & !(1 << e_p_pawn);
-        if unsafe { RAYS_BETWEEN[king_sq][from_sq] } & en_passantless_occupancy != 0 {
+        if RAYS_BETWEEN[king_sq][from_sq] & en_passantless_occupancy != 0 {
             return (false, 0);
         }
-        let pinners_area: u64 = unsafe { RAYS_FROM[king_sq][from_sq] };
+        let pinners_area: u64 = RAYS_FROM[king_sq][from_sq];
         let is_line: bool = (from_sq % 8 == king_sq % 8) || (from_sq / 8 == king_sq / 8);
         let attackers: u64 = if king_color == 8 {
             if is_line {
@@ -63,12 +64,12 @@ impl Board {
         let king_sq: usize = king_square as usize;
         let from_sq: usize = from as usize;
 
-        if unsafe { TWO_SQUARES_LINE[king_sq][from_sq] == 0 } {
+        if TWO_SQUARES_LINE[king_sq][from_sq] == 0 {
             return (false, 0);
         }
 
         let occ_no_moving_piece: u64 = self.total_occupancy & !(1 << from);
-        if occ_no_moving_piece & unsafe { RAYS_BETWEEN[king_sq][from_sq] } != 0 {
+        if occ_no_moving_piece & RAYS_BETWEEN[king_sq][from_sq] != 0 {
             return (false, 0);
         }
 
@@ -97,27 +98,25 @@ impl Board {
         return match (diagonal_attacker, linear_attacker) {
             (64, 64) => (false, 0),
             (_, 64) => {
-                if unsafe {
-                    TWO_SQUARES_LINE[king_sq][from as usize]
-                        != TWO_SQUARES_LINE[king_sq][diagonal_attacker]
-                } {
+                if TWO_SQUARES_LINE[king_sq][from as usize]
+                    != TWO_SQUARES_LINE[king_sq][diagonal_attacker]
+                {
                     return (false, 0);
                 }
                 (
                     true,
-                    unsafe { RAYS_BETWEEN[king_sq][diagonal_attacker] } | diagonal_enemies,
+                    RAYS_BETWEEN[king_sq][diagonal_attacker] | diagonal_enemies,
                 )
             }
             (64, _) => {
-                if unsafe {
-                    TWO_SQUARES_LINE[king_sq][from as usize]
-                        != TWO_SQUARES_LINE[king_sq][linear_attacker]
-                } {
+                if TWO_SQUARES_LINE[king_sq][from as usize]
+                    != TWO_SQUARES_LINE[king_sq][linear_attacker]
+                {
                     return (false, 0);
                 }
                 (
                     true,
-                    unsafe { RAYS_BETWEEN[king_sq][linear_attacker] } | linear_enemies,
+                    RAYS_BETWEEN[king_sq][linear_attacker] | linear_enemies,
                 )
             }
             _ => (true, 0),
@@ -154,13 +153,7 @@ impl Board {
                 check_squares[moving_piece as usize - 1]
             }
         } else if flag == 1 {
-            let to_idx: usize = to as usize;
-            let rook_idx: usize = if from > to_idx {
-                to_idx + 1
-            } else {
-                to_idx - 1
-            };
-            let rook_bb: u64 = 1 << rook_idx;
+            let rook_bb: u64 = 1 << castling_destinations(from as u8, to as u8).1;
             let rook_check_squares: u64 = check_squares[3];
 
             if rook_check_squares & rook_bb != 0 {
@@ -177,7 +170,7 @@ impl Board {
             return flag + 7;
         }
 
-        let squares: u64 = if unsafe { TWO_SQUARES_LINE[king_square][from] } != 0 {
+        let squares: u64 = if TWO_SQUARES_LINE[king_square][from] != 0 {
             let diag_discovery_attacks: usize = ((bishop_attacks(king_square, total_occ)
                 & !to_sq_bb)
                 & (self.bitboards[queen_idx] | self.bitboards[bishop_idx]))
@@ -186,16 +179,12 @@ impl Board {
                 & (self.bitboards[queen_idx] | self.bitboards[rook_idx]))
                 .trailing_zeros() as usize;
 
-            unsafe {
-                if diag_discovery_attacks != 64 {
-                    RAYS_BETWEEN[king_square][diag_discovery_attacks]
-                        | (1 << diag_discovery_attacks)
-                } else if line_discovery_attacks != 64 {
-                    RAYS_BETWEEN[king_square][line_discovery_attacks]
-                        | (1 << line_discovery_attacks)
-                } else {
-                    64
-                }
+            if diag_discovery_attacks != 64 {
+                RAYS_BETWEEN[king_square][diag_discovery_attacks] | (1 << diag_discovery_attacks)
+            } else if line_discovery_attacks != 64 {
+                RAYS_BETWEEN[king_square][line_discovery_attacks] | (1 << line_discovery_attacks)
+            } else {
+                64
             }
         } else {
             64
@@ -261,7 +250,8 @@ impl Board {
                     &state.check_squares,
                 );
                 if captures_checks_only
-                    && self.cached_pieces[final_pos as usize] == 0 && check_flag == 0
+                    && self.cached_pieces[final_pos as usize] == 0
+                    && check_flag == 0
                 {
                     dest_bitboard &= dest_bitboard - 1;
                     continue;
@@ -470,6 +460,11 @@ impl Board {
 
     #[inline(always)]
     pub fn is_square_attacked(&self, square: u8, by: u16) -> bool {
+        return self.is_square_attacked_with_occupancy(square, by, self.total_occupancy);
+    }
+
+    #[inline(always)]
+    pub fn is_square_attacked_with_occupancy(&self, square: u8, by: u16, occupancy: u64) -> bool {
         let usize_square: usize = square as usize;
         let (w_q, b_q) = (&self.bitboards[4], &self.bitboards[10]);
         let (
@@ -500,7 +495,7 @@ impl Board {
                 self.bitboards[5],
             ),
         };
-        let total_occ: u64 = self.total_occupancy & !defending_king; // KING IS NOT A DEFENDER!
+        let total_occ: u64 = occupancy & !defending_king; // KING IS NOT A DEFENDER!
         if (KNIGHT_ATTACKS[usize_square] & attacking_knights != 0)
             | (attacking_pawns & pawn_attacks != 0)
             | (bishop_attacks(usize_square, total_occ) & diagonal_attackers != 0)
@@ -512,6 +507,99 @@ impl Board {
         return false;
     }
 
+    // pieces of both colors attacking the square through the given occupancy
+    #[inline(always)]
+    pub fn attackers_to(&self, square: usize, occupancy: u64) -> u64 {
+        let bbs: &[u64; 12] = &self.bitboards;
+        let (diagonal_sliders, linear_sliders): (u64, u64) = (
+            bbs[2] | bbs[8] | bbs[4] | bbs[10],
+            bbs[3] | bbs[9] | bbs[4] | bbs[10],
+        );
+        return (BLACK_PAWN_ATTACKS[square] & bbs[0])
+            | (WHITE_PAWN_ATTACKS[square] & bbs[6])
+            | (KNIGHT_ATTACKS[square] & (bbs[1] | bbs[7]))
+            | (bishop_attacks(square, occupancy) & diagonal_sliders)
+            | (rook_attacks(square, occupancy) & linear_sliders)
+            | (KING_ATTACKS[square] & (bbs[5] | bbs[11]));
+    }
+
+    // static exchange evaluation: material won by the moving side if both sides keep recapturing
+    // on the target square with their least valuable piece, and either may stop when it pays off.
+    // sliders hidden behind the pieces that already took part join the exchange as x-rays
+    pub fn see(&self, m: u16) -> i32 {
+        if is_castling(m) {
+            return 0;
+        }
+        let (from, to): (usize, usize) = (from_square(m) as usize, to_square(m) as usize);
+        let flag: u16 = without_check_flag(m) >> MARK_SHIFT;
+        let moving_piece: u16 = self.cached_pieces[from];
+        let mut occupancy: u64 = self.total_occupancy & !(1 << from);
+
+        let mut gains: [i32; 32] = [0; 32];
+        gains[0] = if flag == 2 {
+            // the pawn taken en passant is not on the target square
+            occupancy &= !(1 << if moving_piece < 7 { to - 8 } else { to + 8 });
+            PAWN_VALUE
+        } else {
+            Self::see_value(self.cached_pieces[to])
+        };
+        let mut piece_on_square_value: i32 = Self::see_value(moving_piece);
+        if (3..7).contains(&flag) {
+            piece_on_square_value = VALUE_TABLE[flag as usize - 2];
+            gains[0] += piece_on_square_value - PAWN_VALUE;
+        }
+
+        let (diagonal_sliders, linear_sliders): (u64, u64) = (
+            self.bitboards[2] | self.bitboards[8] | self.bitboards[4] | self.bitboards[10],
+            self.bitboards[3] | self.bitboards[9] | self.bitboards[4] | self.bitboards[10],
+        );
+        let mut attackers: u64 = self.attackers_to(to, occupancy) & occupancy;
+        let mut side_bitboards: usize = if moving_piece < 7 { 6 } else { 0 };
+        let mut exchanges: usize = 0;
+        loop {
+            let side_attackers: u64 = attackers & self.occupancies[side_bitboards / 6];
+            if side_attackers == 0 {
+                break;
+            }
+            let (mut attacker_idx, mut attacker_square): (usize, u64) = (0, 0);
+            for i in 0..6 {
+                let candidates: u64 = side_attackers & self.bitboards[side_bitboards + i];
+                if candidates != 0 {
+                    (attacker_idx, attacker_square) = (i, candidates & candidates.wrapping_neg());
+                    break;
+                }
+            }
+            // the king can only take the last piece, when no enemy attacker is left
+            if attacker_idx == 5 && (attackers & !side_attackers) != 0 {
+                break;
+            }
+            exchanges += 1;
+            gains[exchanges] = piece_on_square_value - gains[exchanges - 1];
+            piece_on_square_value = VALUE_TABLE[attacker_idx];
+
+            occupancy &= !attacker_square;
+            attackers = (attackers
+                | (bishop_attacks(to, occupancy) & diagonal_sliders)
+                | (rook_attacks(to, occupancy) & linear_sliders))
+                & occupancy;
+            side_bitboards = 6 - side_bitboards;
+        }
+        while exchanges > 0 {
+            gains[exchanges - 1] = -(-gains[exchanges - 1]).max(gains[exchanges]);
+            exchanges -= 1;
+        }
+        return gains[0];
+    }
+
+    #[inline(always)]
+    fn see_value(piece: u16) -> i32 {
+        return if piece == 0 {
+            0
+        } else {
+            VALUE_TABLE[(piece as usize - 1) % 6]
+        };
+    }
+
     #[inline(always)]
     pub fn type_of_check(&self, from: u16, to: u16, king_square: u8, king_color: u16) -> u8 {
         // check in CURRENT position
@@ -574,83 +662,42 @@ impl Board {
         if self.is_square_attacked(initial_pos as u8, opposite_color) || captures_only {
             return;
         }
-        let (castling_squares, mut kingside_path, mut queenside_path): (
-            (Option<u8>, Option<u8>),
-            u64,
-            u64,
-        ) = match color {
-            8 => match (
-                &state.castling_rights & WHITE_SHORT_MASK != 0,
-                &state.castling_rights & WHITE_LONG_MASK != 0,
-            ) {
-                (true, true) => (
-                    (Some(6), Some(2)),
-                    0b0000000000000000000000000000000000000000000000000000000001100000,
-                    0b0000000000000000000000000000000000000000000000000000000000001110,
-                ),
-                (false, false) => return,
-                (false, true) => (
-                    (None, Some(2)),
-                    0,
-                    0b0000000000000000000000000000000000000000000000000000000000001110,
-                ),
-                (true, false) => (
-                    (Some(6), None),
-                    0b0000000000000000000000000000000000000000000000000000000001100000,
-                    0,
-                ),
-            },
-            _ => match (
-                &state.castling_rights & BLACK_SHORT_MASK != 0,
-                &state.castling_rights & BLACK_LONG_MASK != 0,
-            ) {
-                (true, true) => (
-                    (Some(62), Some(58)),
-                    0b0110000000000000000000000000000000000000000000000000000000000000,
-                    0b0000111000000000000000000000000000000000000000000000000000000000,
-                ),
-                (false, false) => return,
-                (true, false) => (
-                    (Some(62), None),
-                    0b0110000000000000000000000000000000000000000000000000000000000000,
-                    0,
-                ),
-                (false, true) => (
-                    (None, Some(58)),
-                    0,
-                    0b0000111000000000000000000000000000000000000000000000000000000000,
-                ),
-            },
-        };
-
-        if queenside_path != 0 && (queenside_path & self.total_occupancy == 0) {
-            let mut finished_fully: bool = true;
-            while queenside_path != 0 {
-                let square: u8 = queenside_path.trailing_zeros() as u8;
-                if self.is_square_attacked(square, opposite_color) && ((1 << square) & FILE_B == 0)
-                {
-                    finished_fully = false;
-                    break;
-                }
-                queenside_path &= queenside_path - 1;
+        // the king takes its own rook in the encoding. the squares both pieces cross must be
+        // empty, and the king may not pass through check. the castling rook is lifted off the
+        // board first, since in chess960 it may be shielding the king's destination
+        let rights_offset: usize = if color == 8 { 0 } else { 2 };
+        for right_index in [rights_offset + 1, rights_offset] {
+            if state.castling_rights & (1 << right_index) == 0 {
+                continue;
             }
-            if finished_fully && let Some(sq) = castling_squares.1 {
-                moves.push(initial_pos | ((sq as u16) << TO_SHIFT) | (0b0001 << MARK_SHIFT));
+            let rook_square: u8 = state.castling_rooks[right_index];
+            let (king_to, rook_to): (u8, u8) =
+                castling_destinations(initial_pos as u8, rook_square);
+            let king_path: u64 = rank_span(initial_pos as u8, king_to);
+            let must_be_empty: u64 = (king_path | rank_span(rook_square, rook_to))
+                & !((1 << initial_pos) | (1 << rook_square));
+            if must_be_empty & self.total_occupancy != 0 {
+                continue;
             }
-        }
-        if kingside_path != 0 && (kingside_path & self.total_occupancy == 0) {
-            let mut finished_fully: bool = true;
-            while kingside_path != 0 {
-                let square: u8 = kingside_path.trailing_zeros() as u8;
-                if self.is_square_attacked(square, opposite_color) && ((1 << square) & FILE_B == 0)
-                {
-                    finished_fully = false;
+            let occupancy_without_rook: u64 = self.total_occupancy & !(1 << rook_square);
+            let mut unchecked_path: u64 = king_path & !(1 << initial_pos);
+            let mut is_path_safe: bool = true;
+            while unchecked_path != 0 {
+                let square: u8 = unchecked_path.trailing_zeros() as u8;
+                if self.is_square_attacked_with_occupancy(
+                    square,
+                    opposite_color,
+                    occupancy_without_rook,
+                ) {
+                    is_path_safe = false;
                     break;
                 }
-                kingside_path &= kingside_path - 1;
+                unchecked_path &= unchecked_path - 1;
             }
-            if finished_fully && let Some(sq) = castling_squares.0 {
-                moves.push(initial_pos | ((sq as u16) << TO_SHIFT) | (0b0001 << MARK_SHIFT));
+            if is_path_safe {
+                moves.push(
+                    initial_pos | ((rook_square as u16) << TO_SHIFT) | (0b0001 << MARK_SHIFT),
+                );
             }
         }
     }
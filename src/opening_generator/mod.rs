@@ -13,6 +13,9 @@ use std::{
 const DEFAULT_OPENING_DEPTH: u8 = 11;
 const MAX_PLIES: u8 = 6; // how many plies (halfmoves) to search from STARTING_POS
 const AVERAGE_MOVES_AMOUNT: usize = 2; // amount of moves per position (can be fewer)
+const FIRST_MOVES_AMOUNT: usize = 5; // more variety in the first two plies
+const GOOD_MOVE_MARGIN: i32 = 10; // how much worse than the best move a book move may be
+const FIRST_GOOD_MOVE_MARGIN: i32 = 50;
 
 const JSON_PATH: &'static str = "opening_book.json";
 
@@ -68,9 +71,17 @@ impl Engine {
                 &mut board,
                 &mut state,
                 DEFAULT_OPENING_DEPTH,
-                if first_move { 5 } else { AVERAGE_MOVES_AMOUNT },
+                if first_move {
+                    FIRST_MOVES_AMOUNT
+                } else {
+                    AVERAGE_MOVES_AMOUNT
+                },
                 entry.color,
-                first_move,
+                if first_move {
+                    FIRST_GOOD_MOVE_MARGIN
+                } else {
+                    GOOD_MOVE_MARGIN
+                },
             );
             let moves_len: usize = best_moves.len();
             if moves_len < 5 {
@@ -81,7 +92,7 @@ impl Engine {
                 opening_map,
                 OpeningResponse {
                     responses: best_moves.clone().try_into().unwrap(),
-                    for_position: Engine::rebuild_hash(&board, entry.color),
+                    for_position: Engine::rebuild_hash(&board, &state, entry.color),
                 },
             );
 
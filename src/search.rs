@@ -2,21 +2,103 @@ use crate::{
     board::Board,
     board_geometry_templates::*,
     constants::{
-        attacks::{INDICES_TO_COORDS, MVV_LVA},
+        attacks::MVV_LVA,
         heuristics::*,
         piece_values::*,
         zobrist_hashes::{BLACK_ZOBRIST_KEY, WHITE_ZOBRIST_KEY, ZOBRIST_HASH_TABLE},
     },
+    converters::{fen_converter::board_to_fen, move_converter::move_to_uci},
     employ_config::load_opening_book,
     gamestate::GameState,
     moves::MoveList,
-    transposition::{TTEntry, TranspositionTable},
+    time_management::{MOVE_OVERHEAD_MS, TimeManager},
+    transposition::{DEFAULT_HASH_MB, TTEntry, TranspositionTable},
 };
+use once_cell::sync::Lazy;
 use rand::{rng, seq::IndexedRandom};
 use std::{
     collections::HashMap,
-    time::{Duration, Instant},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
 };
+
+// a root move with its score and the principal variation starting from it
+#[derive(Clone)]
+pub struct RootLine {
+    pub score: i32,
+    pub flag: u8, // same as TTEntry::flag: 0 exact, 1 lowerbound, 2 upperbound
+    pub line: Vec<u16>,
+}
+
+// what "go" asked for. the root loop honors every limit, the node limit
+// also stops negamax and quiescence search in the middle of an iteration
+pub struct SearchLimits {
+    pub depth: u8,
+    pub nodes: Option<u64>,
+    pub mate: Option<u8>, // stop once a mate in this many moves is found
+    pub time_manager: TimeManager,
+    pub search_moves: Vec<u16>, // "go searchmoves", empty means all root moves
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u8) -> Self {
+        return Self {
+            depth,
+            nodes: None,
+            mate: None,
+            time_manager: TimeManager::infinite(),
+            search_moves: Vec::new(),
+        };
+    }
+}
+
+// flags shared between the UCI thread and the search thread
+pub struct SearchSignals {
+    pub stop: AtomicBool,
+    pub infinite: AtomicBool, // "go infinite": no time limit, wait for "stop"
+    pub pondering: AtomicBool, // "go ponder": no time limit until "ponderhit"
+    pub ponderhit_ms: AtomicU64, // when "ponderhit" came, counting from the start of the search
+    pub helpers_stop: AtomicBool, // the main thread is done, Lazy SMP helper threads stop too
+    pub helper_nodes: AtomicU64, // nodes searched by the helper threads, reported in batches
+    pub main_nodes: AtomicU64, // the same for the main thread, for the helpers' "go nodes" check
+}
+
+impl SearchSignals {
+    pub fn new() -> Self {
+        return Self {
+            stop: AtomicBool::new(false),
+            infinite: AtomicBool::new(false),
+            pondering: AtomicBool::new(false),
+            ponderhit_ms: AtomicU64::new(0),
+            helpers_stop: AtomicBool::new(false),
+            helper_nodes: AtomicU64::new(0),
+            main_nodes: AtomicU64::new(0),
+        };
+    }
+
+    #[inline(always)]
+    pub fn must_wait(&self) -> bool {
+        return (self.infinite.load(Ordering::Relaxed) || self.pondering.load(Ordering::Relaxed))
+            && !self.stop.load(Ordering::Relaxed);
+    }
+}
+
+// stops the helpers when the main thread leaves its search, also when it panics,
+// as the scope would otherwise wait for them forever
+struct HelpersStopGuard(Arc<SearchSignals>);
+
+impl Drop for HelpersStopGuard {
+    fn drop(&mut self) -> () {
+        self.0.helpers_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+pub const PIECE_SQUARES: usize = 12 * 64; // a piece landing on a square, see Engine::piece_square
+pub type ContinuationHistory = Box<[[i16; PIECE_SQUARES]]>; // [earlier piece-square][piece-square]
+
 pub struct Engine {
     pub side: u16, // which color Ferrous plays
     pub depth: u8,
@@ -25,24 +107,119 @@ pub struct Engine {
     pub move_lists: [MoveList; 128],
     pub move_scores: [[i16; 192]; 128],
     pub history_heuristics: [i16; 4096],
+    pub counter_moves: [u16; PIECE_SQUARES], // the quiet move that refuted a piece landing on a square
+    pub continuation_history: [ContinuationHistory; 2], // quiet moves following the moves 1 and 2 plies earlier
+    pub played_piece_squares: [Option<usize>; 128], // per ply, the piece-square of the move searched there
+    pub capture_history: [i16; PIECE_SQUARES * 6], // [piece-square][captured piece type], see capture_key
     pub current_hash: u64,
-    pub transposition_table: TranspositionTable,
+    pub transposition_table: Arc<TranspositionTable>, // shared by all the search threads
     pub nodes_since_last_check: u64,
-    pub how_much_searched: (f32, f32), /* First: how many root move searched,
-                                       second how many root moves to search.
-                                       By applying the formula (how_much_searched.0 / how_much_searched.1)
-                                       the engine can determine whether to end the session or not */
+    pub node_limit: u64,
+    pub time_manager: TimeManager,
     pub opening_book: HashMap<u64, [Option<u16>; 5]>,
+    pub signals: Arc<SearchSignals>,
+    pub pv_table: [[u16; 128]; 128], // triangular table, pv_table[ply][ply..pv_length[ply]]
+    pub pv_length: [usize; 128],
+    pub pv_lines: Vec<RootLine>, // best "multi_pv" lines of the last finished iteration, best first
+    pub seldepth: usize,
+    pub threads: usize,
+    pub multi_pv: usize,
+    pub own_book: bool,
+    pub move_overhead_ms: u64,
+    pub chess960: bool, // castling is written as the king taking its rook
+    pub null_move_min_ply: usize, // null moves are off below this ply while verifying a null move cutoff
+    pub excluded_moves: [u16; 128], // per ply, the TT move left out while checking that it is singular
+    pub thread_id: usize,           // 0 is the main thread, which alone talks to the GUI
+    pub completed_depth: u8,        // depth of the last finished iteration
+    pub searched_nodes: u64,        // nodes of the last search, the helpers' included
+    pub contempt: i32, // how much worse than equal a draw is for Ferrous, negative to seek draws
+    pub aspiration_windows: bool, // off only to compare against full window searches
 }
 
-const CHECKMATE_VALUE: i32 = 1_000_000;
+pub const CHECKMATE_VALUE: i32 = 1_000_000;
+pub const MAX_PLY: usize = 128; // size of the per-ply tables
+pub const MAX_SEARCH_DEPTH: u8 = 64; // extensions need the rest of MAX_PLY
+pub const MATE_SCORE_THRESHOLD: i32 = CHECKMATE_VALUE - MAX_PLY as i32; // scores beyond it are mates
+pub const SEARCH_THREAD_STACK_SIZE: usize = 64 * 1024 * 1024;
 const TIME_CHECK_NODES_OFFSET: u64 = 2500; // how often to check for time
 const TIMEOUT_RETURN: i32 = 2_000_001;
 const QUIESCENCE_DELTA: i32 = 50;
-const MAX_HISTORY_SCORE: i16 = 2000;
+pub const DEFAULT_CONTEMPT: i32 = 10;
+pub const MAX_HISTORY_SCORE: i16 = 2000;
+pub const COUNTERMOVE_BONUS: i16 = 60; // below the killers
+pub const CAPTURE_HISTORY_DIVISOR: i16 = 20; // the full history is worth one MVV-LVA step
+const CURRMOVE_INFO_DELAY_MS: u128 = 3000; // GUIs are not flooded with "currmove" in short searches
+const REVERSE_FUTILITY_MAX_DEPTH: u8 = 6;
+const REVERSE_FUTILITY_MARGIN: i32 = 80; // per ply of depth left
+const FUTILITY_MAX_DEPTH: u8 = 3;
+const FUTILITY_MARGIN: i32 = 120; // per ply of depth left
+const RAZORING_MAX_DEPTH: u8 = 3;
+const RAZORING_MARGIN: i32 = 250; // per ply of depth left
+const ASPIRATION_MIN_DEPTH: u8 = 4;
+const ASPIRATION_WINDOW: i32 = 30; // doubles on every fail
+const ASPIRATION_MAX_WINDOW: i32 = 500; // past it the window opens fully
+const NULL_MOVE_MIN_DEPTH: u8 = 3;
+const NULL_MOVE_BASE_REDUCTION: u8 = 3; // grows with the depth and the static eval margin over beta
+const NULL_MOVE_EVAL_MARGIN: i32 = 200; // one more ply of reduction per this much over beta, up to 3
+const NULL_MOVE_VERIFICATION_DEPTH: u8 = 10;
+const IIR_MIN_DEPTH: u8 = 4;
+const EXTENDED_PATH_FACTOR: usize = 2; // no extensions once a path is this many times the root depth
+const SINGULAR_MIN_DEPTH: u8 = 6;
+const SINGULAR_TT_DEPTH_MARGIN: u8 = 3; // how much shallower than the node the TT entry may be
+const SINGULAR_MARGIN: i32 = 2; // per ply of depth, how far below the TT score the rest must stay
+const LATE_MOVE_PRUNING_MAX_DEPTH: u8 = 3;
+const LATE_MOVE_PRUNING_BASE: usize = 3; // quiet moves go after this many moves plus depth squared
+const HISTORY_PRUNING_MAX_DEPTH: u8 = 3;
+const HISTORY_PRUNING_MARGIN: i32 = 600; // per ply of depth left, how negative the history must be
+// late move reductions, in plies: LMR_BASE + ln(depth) * ln(move number) / LMR_DIVISOR, then adjusted
+pub const LMR_BASE: f64 = 0.75;
+pub const LMR_DIVISOR: f64 = 2.25;
+pub const LMR_MIN_MOVES: usize = 3; // the first moves are never reduced
+pub const LMR_PV_NODE: i32 = 1; // less in the principal variation
+pub const LMR_IN_CHECK: i32 = 1; // less for evasions
+pub const LMR_KILLER: i32 = 1;
+pub const LMR_GIVES_CHECK: i32 = 1;
+pub const LMR_HISTORY_DIVISOR: i32 = 1000; // one ply less per this much history, more when negative
+
+// LMR_TABLE[depth][move number]
+pub static LMR_TABLE: Lazy<[[u8; 64]; 64]> = Lazy::new(|| {
+    let mut table: [[u8; 64]; 64] = [[0; 64]; 64];
+    for depth in 1..64 {
+        for moves in 1..64 {
+            table[depth][moves] =
+                (LMR_BASE + (depth as f64).ln() * (moves as f64).ln() / LMR_DIVISOR) as u8;
+        }
+    }
+    table
+});
+
+// "cp <x>" or "mate <moves>", negative when the engine is getting mated
+pub fn score_to_uci(score: i32) -> String {
+    if score >= MATE_SCORE_THRESHOLD {
+        return format!("mate {}", (CHECKMATE_VALUE - score + 1) / 2);
+    }
+    if score <= -MATE_SCORE_THRESHOLD {
+        return format!("mate -{}", (CHECKMATE_VALUE + score) / 2);
+    }
+    return format!("cp {score}");
+}
 
 impl Engine {
     pub fn new(side: u16, depth: u8) -> Self {
+        return Self::with_table(
+            side,
+            depth,
+            Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            load_opening_book(),
+        );
+    }
+
+    fn with_table(
+        side: u16,
+        depth: u8,
+        transposition_table: Arc<TranspositionTable>,
+        opening_book: HashMap<u64, [Option<u16>; 5]>,
+    ) -> Self {
         return Engine {
             side,
             depth,
@@ -53,14 +230,60 @@ impl Engine {
                 first_not_occupied: 0,
             }; 128],
             history_heuristics: [0; 4096],
+            counter_moves: [0; PIECE_SQUARES],
+            continuation_history: [
+                Self::empty_continuation_history(),
+                Self::empty_continuation_history(),
+            ],
+            played_piece_squares: [None; 128],
+            capture_history: [0; PIECE_SQUARES * 6],
             move_scores: [[0; 192]; 128],
             current_hash: 0,
-            transposition_table: TranspositionTable::new(),
+            transposition_table,
             nodes_since_last_check: 0,
-            how_much_searched: (0., 0.),
-            opening_book: load_opening_book(),
+            node_limit: u64::MAX,
+            time_manager: TimeManager::infinite(),
+            opening_book,
+            signals: Arc::new(SearchSignals::new()),
+            pv_table: [[0; 128]; 128],
+            pv_length: [0; 128],
+            pv_lines: Vec::new(),
+            seldepth: 0,
+            threads: 1,
+            multi_pv: 1,
+            own_book: true,
+            move_overhead_ms: MOVE_OVERHEAD_MS,
+            chess960: false,
+            null_move_min_ply: 0,
+            excluded_moves: [0; 128],
+            thread_id: 0,
+            completed_depth: 0,
+            searched_nodes: 0,
+            contempt: DEFAULT_CONTEMPT,
+            aspiration_windows: true,
         };
     }
+
+    // a Lazy SMP helper: the same table and signals, but its own killers, history and move lists.
+    // it never plays from the book, so none is loaded
+    fn helper(&self, thread_id: usize, board: &mut Board, state: &mut GameState) -> Self {
+        let mut helper: Engine = Engine {
+            signals: self.signals.clone(),
+            thread_id,
+            own_book: false,
+            chess960: self.chess960,
+            contempt: self.contempt,
+            aspiration_windows: self.aspiration_windows,
+            ..Self::with_table(
+                self.side,
+                self.depth,
+                self.transposition_table.clone(),
+                HashMap::new(),
+            )
+        };
+        helper.prepare_before_search(board, state);
+        return helper;
+    }
     #[inline(always)]
     pub fn evaluate(&mut self, board: &Board) -> () {
         self.evaluation = 0;
@@ -150,15 +373,101 @@ impl Engine {
         board.king_moves(&state, color, &mut self.move_lists[depth], captures_only);
     }
 
-    fn add_killer(&mut self, killer: u16, depth: u8) {
-        let depth: usize = depth as usize;
+    pub fn empty_continuation_history() -> ContinuationHistory {
+        return vec![[0; PIECE_SQUARES]; PIECE_SQUARES].into_boxed_slice();
+    }
+
+    // the moving piece and the destination, read before the move is made
+    #[inline(always)]
+    pub fn piece_square(board: &Board, m: u16) -> usize {
+        return (board.cached_pieces[from_square(m) as usize] as usize - 1) * 64
+            + to_square(m) as usize;
+    }
+
+    // history gravity: the step shrinks as the entry nears the limit, so it never saturates
+    #[inline(always)]
+    fn apply_history_bonus(entry: &mut i16, bonus: i32) -> () {
+        let max: i32 = MAX_HISTORY_SCORE as i32;
+        let bonus: i32 = bonus.clamp(-max, max);
+        *entry += (bonus - *entry as i32 * bonus.abs() / max) as i16;
+    }
+
+    // the capturing piece, its destination and the type of the captured piece, before the move is made
+    #[inline(always)]
+    pub fn capture_key(board: &Board, m: u16) -> usize {
+        let victim: usize = board.cached_pieces[to_square(m) as usize] as usize - 1;
+        return Self::piece_square(board, m) * 6 + victim % 6;
+    }
 
-        if self.killer_moves[depth][1].is_some() {
+    // the capture that caused a beta cutoff gains capture history, the ones searched before it lose some
+    fn update_capture_history(
+        &mut self,
+        depth: u8,
+        cutoff_capture: Option<usize>,
+        failed_captures: &[usize],
+    ) -> () {
+        let bonus: i32 = depth as i32 * depth as i32;
+        if let Some(capture_key) = cutoff_capture {
+            Self::apply_history_bonus(&mut self.capture_history[capture_key], bonus);
+        }
+        for capture_key in failed_captures {
+            Self::apply_history_bonus(&mut self.capture_history[*capture_key], -bonus);
+        }
+    }
+
+    // the history of a quiet move on its own and after the previous move
+    #[inline(always)]
+    fn quiet_history(&self, ply: usize, m: u16, piece_square: usize) -> i32 {
+        let mut history: i32 = self.history_heuristics
+            [((m & FROM_MASK) as usize) << 6 | ((m & TO_MASK) >> TO_SHIFT) as usize]
+            as i32;
+        if let Some(previous) = ply
+            .checked_sub(1)
+            .and_then(|p| self.played_piece_squares[p])
+        {
+            history += self.continuation_history[0][previous][piece_square] as i32;
+        }
+        return history;
+    }
+
+    // the quiet move that caused a beta cutoff becomes the countermove of the previous move and
+    // gains continuation history, the quiet moves searched before it without a cutoff lose some
+    fn update_continuations(
+        &mut self,
+        ply: usize,
+        depth: u8,
+        cutoff_move: u16,
+        cutoff_piece_square: usize,
+        failed_quiets: &[usize],
+    ) -> () {
+        let bonus: i32 = depth as i32 * depth as i32;
+        for plies_back in 1..=2 {
+            let Some(previous) = ply
+                .checked_sub(plies_back)
+                .and_then(|p| self.played_piece_squares[p])
+            else {
+                continue;
+            };
+            if plies_back == 1 {
+                self.counter_moves[previous] = cutoff_move;
+            }
+            let table: &mut [i16; PIECE_SQUARES] =
+                &mut self.continuation_history[plies_back - 1][previous];
+            Self::apply_history_bonus(&mut table[cutoff_piece_square], bonus);
+            for piece_square in failed_quiets {
+                Self::apply_history_bonus(&mut table[*piece_square], -bonus);
+            }
+        }
+    }
+
+    // killers are kept per ply, where the move ordering and the pruning look them up
+    fn add_killer(&mut self, killer: u16, ply: usize) {
+        if self.killer_moves[ply][0] == Some(killer) {
             return;
         }
 
-        self.killer_moves[depth][1] = self.killer_moves[depth][0];
-        self.killer_moves[depth][0] = Some(killer);
+        self.killer_moves[ply][1] = self.killer_moves[ply][0];
+        self.killer_moves[ply][0] = Some(killer);
     }
 
     pub fn negamax(
@@ -171,30 +480,69 @@ impl Engine {
         mut beta: i32,
         state: &mut GameState,
         node_count: &mut u64,
-        start_time: &Instant,
-        time_limit_ms: &u128,
         max_depth: usize,
     ) -> i32 {
+        let mut depth: u8 = depth;
+        if self.is_node_limit_reached(*node_count) {
+            return TIMEOUT_RETURN;
+        }
         *node_count += 1;
+        let is_pv_node: bool = beta - alpha > 1;
+        self.pv_length[ply] = ply;
+        self.seldepth = self.seldepth.max(ply);
+        self.debug_assert_hash(board, state, color);
 
         let nodes_since_check: &mut u64 = &mut self.nodes_since_last_check;
         *nodes_since_check += 1;
         if *nodes_since_check >= TIME_CHECK_NODES_OFFSET {
             *nodes_since_check = 0;
+            self.report_nodes();
 
-            if start_time.elapsed().as_millis() >= *time_limit_ms && !self.proceed_search(depth) {
+            if self.is_time_over() {
                 return TIMEOUT_RETURN;
             }
         }
 
-        let tt_entry: Option<TTEntry> = self.transposition_table.get_entry(&self.current_hash);
+        if state.is_draw_by_rule(self.current_hash) || board.is_insufficient_material() {
+            return self.draw_score(color);
+        }
+
+        // mate distance pruning: even mating right here cannot beat a shorter mate found before
+        alpha = alpha.max(-CHECKMATE_VALUE + ply as i32);
+        beta = beta.min(CHECKMATE_VALUE - ply as i32 - 1);
+        if alpha >= beta {
+            return alpha;
+        }
+
+        let enemy_color: u16 = if color == 8 { 16 } else { 8 };
+        let in_check: bool = board.is_square_attacked(
+            if color == 8 {
+                board.white_king_square
+            } else {
+                board.black_king_square
+            },
+            enemy_color,
+        );
+        // extensions stop once the path grows too long, so they cannot blow the search up
+        let can_extend: bool =
+            ply + (depth as usize) < (EXTENDED_PATH_FACTOR * max_depth).min(MAX_PLY - 1);
+        if in_check && can_extend {
+            depth += 1;
+        }
+
+        // while checking a TT move for singularity, the node is not the same as with every move
+        let excluded_move: u16 = self.excluded_moves[ply];
+        // the stored bound is judged against the window the node was called with, not the one
+        // the TT entry narrowed it to
+        let (original_alpha, original_beta) = (alpha, beta);
+        let tt_entry: Option<TTEntry> = self.transposition_table.get_entry(&self.current_hash, ply);
         let best_move_transposition: u16 = if let Some(entry) = tt_entry {
             let tt_score: i32 = entry.score;
-            if entry.depth >= depth as usize {
+            if entry.depth >= depth as usize && excluded_move == 0 {
                 match entry.flag {
                     0 => return tt_score,
-                    2 => alpha = alpha.max(tt_score),
-                    1 => beta = beta.min(tt_score),
+                    1 => alpha = alpha.max(tt_score),
+                    2 => beta = beta.min(tt_score),
                     _ => (),
                 }
                 if alpha >= beta {
@@ -206,8 +554,6 @@ impl Engine {
             0
         };
 
-        let enemy_color: u16 = if color == 8 { 16 } else { 8 };
-
         if depth == 0 {
             return self.quiescence_search(
                 board,
@@ -220,9 +566,149 @@ impl Engine {
                 node_count,
             );
         }
+        depth -= Self::iir_reduction(depth, best_move_transposition, excluded_move);
+        let static_eval: i32 = if color == 8 {
+            self.evaluation
+        } else {
+            -self.evaluation
+        };
+
+        // static eval pruning near the leaves, trusted only outside the principal variation.
+        // a singularity check has to search the other moves, a cutoff would tell nothing about them
+        if !is_pv_node && !in_check && excluded_move == 0 {
+            // reverse futility pruning: so far above beta that no reply is going to bring it down
+            if depth <= REVERSE_FUTILITY_MAX_DEPTH
+                && beta.abs() < MATE_SCORE_THRESHOLD
+                && static_eval - REVERSE_FUTILITY_MARGIN * depth as i32 >= beta
+            {
+                return static_eval - REVERSE_FUTILITY_MARGIN * depth as i32;
+            }
+            // razoring: so far below alpha that only captures may help, which quiescence search checks
+            if depth <= RAZORING_MAX_DEPTH
+                && alpha.abs() < MATE_SCORE_THRESHOLD
+                && static_eval + RAZORING_MARGIN * depth as i32 <= alpha
+            {
+                let razor_score: i32 = self.quiescence_search(
+                    board,
+                    state,
+                    alpha,
+                    alpha + 1,
+                    max_depth + 1,
+                    ply + 1,
+                    color,
+                    node_count,
+                );
+                if razor_score.abs() == TIMEOUT_RETURN || razor_score <= alpha {
+                    return razor_score;
+                }
+            }
+        }
+
+        // null move pruning: if passing the turn still fails high, a real move would too.
+        // zugzwang makes passing the best option, so it is avoided with only pawns left.
+        // never in the principal variation, whose score and line must come from real moves
+        if depth >= NULL_MOVE_MIN_DEPTH
+            && !is_pv_node
+            && ply >= self.null_move_min_ply
+            && excluded_move == 0
+            && !in_check
+            && static_eval >= beta
+            && beta.abs() < MATE_SCORE_THRESHOLD
+            && Self::has_non_pawn_material(board, color)
+            && state
+                .moves_history
+                .last()
+                .is_none_or(|m| m.moved_piece != NULL_MOVE)
+        {
+            let reduction: u8 = NULL_MOVE_BASE_REDUCTION
+                + depth / 4
+                + ((static_eval - beta) / NULL_MOVE_EVAL_MARGIN).min(3) as u8;
+            let null_depth: u8 = depth.saturating_sub(1 + reduction);
+
+            self.played_piece_squares[ply] = None;
+            board.perform_null_move(state, color, &mut self.current_hash);
+            let null_score: i32 = -self.negamax(
+                board,
+                null_depth,
+                ply + 1,
+                enemy_color,
+                -beta,
+                -beta + 1,
+                state,
+                node_count,
+                max_depth,
+            );
+            board.cancel_null_move(state, &mut self.current_hash);
+            if null_score.abs() == TIMEOUT_RETURN {
+                return null_score;
+            }
+
+            if null_score >= beta {
+                // mates found after passing are not real
+                let null_score: i32 = null_score.min(MATE_SCORE_THRESHOLD - 1);
+                if depth < NULL_MOVE_VERIFICATION_DEPTH || self.null_move_min_ply != 0 {
+                    return null_score;
+                }
+                // deep nodes are verified by a reduced search without null moves for a few plies
+                self.null_move_min_ply = ply + 3 * null_depth as usize / 4;
+                let verification_score: i32 = self.negamax(
+                    board,
+                    null_depth,
+                    ply,
+                    color,
+                    beta - 1,
+                    beta,
+                    state,
+                    node_count,
+                    max_depth,
+                );
+                self.null_move_min_ply = 0;
+                if verification_score.abs() == TIMEOUT_RETURN {
+                    return verification_score;
+                }
+                if verification_score >= beta {
+                    return null_score;
+                }
+            }
+        }
+
+        // singular extension: the TT move is extended when a reduced search of all the other
+        // moves stays below the TT score by a margin, i.e. nothing else comes close to it
+        let mut singular_move: u16 = 0;
+        if let Some(entry) = tt_entry
+            && depth >= SINGULAR_MIN_DEPTH
+            && can_extend
+            && excluded_move == 0
+            && entry.best_move != 0
+            && entry.flag != 2
+            && entry.depth + SINGULAR_TT_DEPTH_MARGIN as usize >= depth as usize
+            && entry.score.abs() < MATE_SCORE_THRESHOLD
+        {
+            let singular_beta: i32 = entry.score - SINGULAR_MARGIN * depth as i32;
+            self.excluded_moves[ply] = entry.best_move;
+            let singular_score: i32 = self.negamax(
+                board,
+                (depth - 1) / 2,
+                ply,
+                color,
+                singular_beta - 1,
+                singular_beta,
+                state,
+                node_count,
+                max_depth,
+            );
+            self.excluded_moves[ply] = 0;
+            self.pv_length[ply] = ply;
+            if singular_score.abs() == TIMEOUT_RETURN {
+                return singular_score;
+            }
+            if singular_score < singular_beta {
+                singular_move = entry.best_move;
+            }
+        }
+
         let mut best_score: i32 = -CHECKMATE_VALUE;
         let mut best_move: u16 = 0;
-        let (original_alpha, original_beta) = (alpha, beta);
 
         self.generate_pseudo_legal_moves(color, &board, &state, ply, false);
 
@@ -235,11 +721,62 @@ impl Engine {
         );
 
         let mut total_moves: usize = 0;
+        let (mut failed_quiets, mut failed_quiets_amount): ([usize; 64], usize) = ([0; 64], 0);
+        let (mut failed_captures, mut failed_captures_amount): ([usize; 32], usize) = ([0; 32], 0);
+        // futility pruning: at frontier nodes far below alpha, quiet moves are not going to raise it
+        let futility_pruning: bool = !is_pv_node
+            && !in_check
+            && depth <= FUTILITY_MAX_DEPTH
+            && alpha.abs() < MATE_SCORE_THRESHOLD
+            && static_eval + FUTILITY_MARGIN * depth as i32 <= alpha;
 
         for i in 0..last_occupied {
             let allegedly_best_move: u16 = self.move_lists[ply].pseudo_moves[i];
+            if allegedly_best_move == excluded_move {
+                continue;
+            }
             let current_mv_quiet: bool = Self::is_quiet(&board.cached_pieces, allegedly_best_move);
+            // quiet moves carry no check flag, so the pruning below never drops a checking move
+            let gives_check: bool = without_check_flag(allegedly_best_move) != allegedly_best_move;
+            let quiet_check: bool = gives_check
+                && Self::is_quiet(
+                    &board.cached_pieces,
+                    without_check_flag(allegedly_best_move),
+                );
+            // captures giving away more than they win are treated like quiet moves below
+            let losing_capture: bool =
+                !current_mv_quiet && !gives_check && board.see(allegedly_best_move) < 0;
+            // one legal move is searched first to tell mates apart
+            if futility_pruning && (current_mv_quiet || losing_capture) && total_moves > 0 {
+                continue;
+            }
 
+            let piece_square: usize = Self::piece_square(board, allegedly_best_move);
+            // late move pruning and history pruning: in shallow nodes, quiet moves sorted late or
+            // with a bad history rarely matter. a legal move is searched first, so mates are still found
+            if current_mv_quiet
+                && !is_pv_node
+                && !in_check
+                && total_moves > 0
+                && best_score > -MATE_SCORE_THRESHOLD
+            {
+                if depth <= LATE_MOVE_PRUNING_MAX_DEPTH
+                    && total_moves >= LATE_MOVE_PRUNING_BASE + (depth as usize * depth as usize)
+                {
+                    continue;
+                }
+                if depth <= HISTORY_PRUNING_MAX_DEPTH
+                    && !self.killer_moves[ply].contains(&Some(allegedly_best_move))
+                    && self.quiet_history(ply, allegedly_best_move, piece_square)
+                        < -HISTORY_PRUNING_MARGIN * depth as i32
+                {
+                    continue;
+                }
+            }
+            let capture_key: Option<usize> = board
+                .is_capture(allegedly_best_move)
+                .then(|| Self::capture_key(board, allegedly_best_move));
+            self.played_piece_squares[ply] = Some(piece_square);
             board.perform_move(
                 allegedly_best_move,
                 state,
@@ -260,62 +797,86 @@ impl Engine {
                 }
             }
             total_moves += 1;
-            let reduction: u8 = if current_mv_quiet {
-                let lmr: u8 = match total_moves {
-                    0..3 => 0,
-                    3..8 => 1,
-                    _ => 2,
-                }; // more aggressive reduction
-                if depth > lmr { lmr } else { 0 }
+            let new_depth: u8 = depth - 1 + (allegedly_best_move == singular_move) as u8;
+            // quiet checks are reduced like quiet moves, only by less
+            let reduction: u8 = if (current_mv_quiet || quiet_check || losing_capture)
+                && total_moves >= LMR_MIN_MOVES
+            {
+                let mut lmr: i32 = LMR_TABLE[(depth as usize).min(63)][total_moves.min(63)] as i32;
+                if is_pv_node {
+                    lmr -= LMR_PV_NODE;
+                }
+                if in_check {
+                    lmr -= LMR_IN_CHECK;
+                }
+                if self.killer_moves[ply].contains(&Some(allegedly_best_move)) {
+                    lmr -= LMR_KILLER;
+                }
+                if gives_check {
+                    lmr -= LMR_GIVES_CHECK;
+                }
+                if current_mv_quiet {
+                    lmr -= self.quiet_history(ply, allegedly_best_move, piece_square)
+                        / LMR_HISTORY_DIVISOR;
+                }
+                lmr.clamp(0, new_depth as i32) as u8
             } else {
                 0
             };
 
+            // PVS: the first move gets the full window, the rest a null window around alpha,
+            // with a full depth and then a full window re-search if they still beat it
             let mut current_score: i32;
-            if reduction > 0 {
+            if total_moves == 1 {
                 current_score = -self.negamax(
                     board,
-                    depth - 1 - reduction,
+                    new_depth,
+                    ply + 1,
+                    enemy_color,
+                    -beta,
+                    -alpha,
+                    state,
+                    node_count,
+                    max_depth,
+                );
+            } else {
+                current_score = -self.negamax(
+                    board,
+                    new_depth - reduction,
                     ply + 1,
                     enemy_color,
                     -alpha - 1,
                     -alpha,
                     state,
                     node_count,
-                    start_time,
-                    time_limit_ms,
                     max_depth,
                 );
-
-                if current_score > alpha {
+                if reduction > 0 && current_score > alpha && current_score.abs() != TIMEOUT_RETURN {
+                    current_score = -self.negamax(
+                        board,
+                        new_depth,
+                        ply + 1,
+                        enemy_color,
+                        -alpha - 1,
+                        -alpha,
+                        state,
+                        node_count,
+                        max_depth,
+                    );
+                }
+                if current_score > alpha && current_score < beta {
                     current_score = -self.negamax(
                         board,
-                        depth - 1,
+                        new_depth,
                         ply + 1,
                         enemy_color,
                         -beta,
                         -alpha,
                         state,
                         node_count,
-                        start_time,
-                        time_limit_ms,
                         max_depth,
                     );
                 }
-            } else {
-                current_score = -self.negamax(
-                    board,
-                    depth - 1,
-                    ply + 1,
-                    enemy_color,
-                    -beta,
-                    -alpha,
-                    state,
-                    node_count,
-                    start_time,
-                    time_limit_ms,
-                    max_depth,
-                );
             }
             if current_score.abs() == TIMEOUT_RETURN {
                 board.cancel_move(state, color, &mut self.evaluation, &mut self.current_hash);
@@ -325,40 +886,63 @@ impl Engine {
             if current_score > best_score {
                 best_score = current_score;
                 best_move = allegedly_best_move;
+                if current_score > alpha {
+                    self.update_pv(ply, allegedly_best_move);
+                }
             }
             board.cancel_move(state, color, &mut self.evaluation, &mut self.current_hash);
 
             alpha = alpha.max(best_score);
             if alpha >= beta {
-                if !board.is_capture(allegedly_best_move) {
-                    self.add_killer(allegedly_best_move, depth);
+                self.update_capture_history(
+                    depth,
+                    capture_key,
+                    &failed_captures[..failed_captures_amount],
+                );
+                if capture_key.is_none() {
+                    self.add_killer(allegedly_best_move, ply);
                     let history: &mut i16 = &mut self.history_heuristics[(((allegedly_best_move
                         & FROM_MASK)
                         as usize)
                         << 6)
                         | ((allegedly_best_move & TO_MASK) >> TO_SHIFT) as usize];
-                    *history += (depth * depth) as i16;
-                    *history = (*history).min(MAX_HISTORY_SCORE);
+                    // in i32, depth * depth no longer fits a u8 with deep or extended searches
+                    *history = (*history as i32 + depth as i32 * depth as i32)
+                        .min(MAX_HISTORY_SCORE as i32) as i16;
+                    self.update_continuations(
+                        ply,
+                        depth,
+                        allegedly_best_move,
+                        piece_square,
+                        &failed_quiets[..failed_quiets_amount],
+                    );
                 }
                 break;
             }
+            if current_mv_quiet && failed_quiets_amount < failed_quiets.len() {
+                failed_quiets[failed_quiets_amount] = piece_square;
+                failed_quiets_amount += 1;
+            }
+            if let Some(capture_key) = capture_key
+                && failed_captures_amount < failed_captures.len()
+            {
+                failed_captures[failed_captures_amount] = capture_key;
+                failed_captures_amount += 1;
+            }
         }
 
         if total_moves < 1 {
-            return if board.is_square_attacked(
-                if color == 8 {
-                    board.white_king_square
-                } else {
-                    board.black_king_square
-                },
-                enemy_color,
-            ) {
+            // the only legal move is the excluded one, which makes it singular
+            if excluded_move != 0 {
+                return alpha;
+            }
+            return if in_check {
                 -CHECKMATE_VALUE + ply as i32
             } else {
-                0
+                self.draw_score(color)
             };
         }
-        if depth >= 1 {
+        if depth >= 1 && excluded_move == 0 {
             let flag: u8 = if best_score >= original_beta {
                 1
             } else if best_score <= original_alpha {
@@ -375,6 +959,7 @@ impl Engine {
                     flag,
                     best_move,
                 },
+                ply,
             );
         }
         return best_score;
@@ -391,7 +976,27 @@ impl Engine {
         color: u16,
         node_count: &mut u64,
     ) -> i32 {
+        if self.is_node_limit_reached(*node_count) {
+            return TIMEOUT_RETURN;
+        }
         *node_count += 1;
+        self.seldepth = self.seldepth.max(ply);
+        self.debug_assert_hash(board, state, color);
+
+        let nodes_since_check: &mut u64 = &mut self.nodes_since_last_check;
+        *nodes_since_check += 1;
+        if *nodes_since_check >= TIME_CHECK_NODES_OFFSET {
+            *nodes_since_check = 0;
+            self.report_nodes();
+
+            if self.is_stopped() {
+                return TIMEOUT_RETURN;
+            }
+        }
+
+        if state.is_draw_by_rule(self.current_hash) || board.is_insufficient_material() {
+            return self.draw_score(color);
+        }
 
         let stand_pat: i32 = if color == 8 {
             self.evaluation
@@ -401,7 +1006,7 @@ impl Engine {
         if depth >= 24 {
             return stand_pat;
         }
-        let tt_entry: Option<TTEntry> = self.transposition_table.get_entry(&self.current_hash);
+        let tt_entry: Option<TTEntry> = self.transposition_table.get_entry(&self.current_hash, ply);
         let best_move_transposition: u16 = if let Some(entry) = tt_entry {
             if entry.depth == 0 {
                 match entry.flag {
@@ -453,6 +1058,7 @@ impl Engine {
 
         let mut best_score: i32 = stand_pat;
         let mut moves_tried: i32 = 0;
+        let (mut failed_captures, mut failed_captures_amount): ([usize; 32], usize) = ([0; 32], 0);
 
         for i in 0..last_occupied {
             let move_to_search: u16 = self.move_lists[ply].pseudo_moves[i];
@@ -477,8 +1083,18 @@ impl Engine {
                 if stand_pat + capture_value + QUIESCENCE_DELTA < alpha {
                     continue;
                 }
+                // losing captures are not going to raise the stand pat
+                if without_check_flag(move_to_search) == move_to_search
+                    && board.see(move_to_search) < 0
+                {
+                    continue;
+                }
             }
 
+            let capture_key: Option<usize> = board
+                .is_capture(move_to_search)
+                .then(|| Self::capture_key(board, move_to_search));
+            self.played_piece_squares[ply] = Some(Self::piece_square(board, move_to_search));
             board.perform_move(
                 move_to_search,
                 state,
@@ -516,6 +1132,10 @@ impl Engine {
 
             board.cancel_move(state, color, &mut self.evaluation, &mut self.current_hash);
 
+            if score.abs() == TIMEOUT_RETURN {
+                return score;
+            }
+
             if score > best_score {
                 best_score = score;
             }
@@ -523,8 +1143,20 @@ impl Engine {
                 alpha = score;
             }
             if alpha >= beta {
+                // the captures here count as searched one ply deep
+                self.update_capture_history(
+                    1,
+                    capture_key,
+                    &failed_captures[..failed_captures_amount],
+                );
                 break;
             }
+            if let Some(capture_key) = capture_key
+                && failed_captures_amount < failed_captures.len()
+            {
+                failed_captures[failed_captures_amount] = capture_key;
+                failed_captures_amount += 1;
+            }
         }
 
         if moves_tried == 0 {
@@ -542,18 +1174,24 @@ impl Engine {
         &mut self,
         board: &Board,
         state: &mut GameState,
-        time_contrainsts: Duration,
-        max_depth: u8,
+        limits: SearchLimits,
     ) -> Option<u16> {
-        let mut node_count: u64 = 0;
-        let mut best_move: Option<u16> = None;
         let mut copied_board: Board = board.clone();
         let mut copied_state: GameState = state.clone();
         copied_state.whose_turn = self.side as u16;
 
         self.prepare_before_search(&mut copied_board, &mut copied_state);
 
-        if let Some(&entry) = self.opening_book.get(&self.current_hash) {
+        // a book move would ignore "searchmoves", and "go mate", "go nodes" and "go infinite"
+        // ask for a search rather than a move
+        let book_allowed: bool = limits.search_moves.is_empty()
+            && limits.mate.is_none()
+            && limits.nodes.is_none()
+            && !self.signals.infinite.load(Ordering::Relaxed);
+        if self.own_book
+            && book_allowed
+            && let Some(&entry) = self.opening_book.get(&self.current_hash)
+        {
             let filtered_items: Vec<u16> = entry.into_iter().flatten().collect();
             if let Some(&mv) = filtered_items.choose(&mut rng()) {
                 std::thread::sleep(Duration::from_millis(200));
@@ -561,15 +1199,77 @@ impl Engine {
             }
         }
 
-        let mut previous_best_move: u16 = 0;
+        // Lazy SMP: the helpers search the same position and share what they find through
+        // the transposition table, their results only count if they get deeper than the main thread
+        let mut helpers: Vec<Engine> = (1..self.threads.max(1))
+            .map(|thread_id| self.helper(thread_id, &mut copied_board, &mut copied_state))
+            .collect();
+        self.signals.helpers_stop.store(false, Ordering::Relaxed);
+        self.signals.helper_nodes.store(0, Ordering::Relaxed);
+        self.signals.main_nodes.store(0, Ordering::Relaxed);
+
+        let mut best_move: Option<u16> = std::thread::scope(|scope| {
+            for helper in helpers.iter_mut() {
+                let helper_limits: SearchLimits = SearchLimits {
+                    depth: limits.depth,
+                    nodes: limits.nodes, // checked against the nodes of all threads
+                    mate: limits.mate,
+                    time_manager: TimeManager::infinite(), // stopped by the main thread instead
+                    search_moves: limits.search_moves.clone(),
+                };
+                let (helper_board, helper_state): (Board, GameState) =
+                    (copied_board.clone(), copied_state.clone());
+                std::thread::Builder::new()
+                    .stack_size(SEARCH_THREAD_STACK_SIZE)
+                    .spawn_scoped(scope, move || {
+                        helper.iterative_deepening(helper_board, helper_state, helper_limits)
+                    })
+                    .unwrap();
+            }
+            let _helpers_stop: HelpersStopGuard = HelpersStopGuard(self.signals.clone());
+            self.iterative_deepening(copied_board, copied_state, limits)
+        });
+        self.searched_nodes += helpers.iter().map(|h| h.searched_nodes).sum::<u64>();
+
+        // the deepest finished iteration is the most reliable one, the better score breaks ties.
+        // helpers search a single line, so with several lines asked only the main thread counts
+        if self.multi_pv <= 1 {
+            for helper in helpers.iter() {
+                let Some(helper_line) = helper.pv_lines.first() else {
+                    continue;
+                };
+                let is_better: bool = match self.pv_lines.first() {
+                    Some(main_line) => {
+                        helper.completed_depth > self.completed_depth
+                            || (helper.completed_depth == self.completed_depth
+                                && helper_line.score > main_line.score)
+                    }
+                    None => true,
+                };
+                if is_better {
+                    best_move = Some(helper_line.line[0]);
+                    self.completed_depth = helper.completed_depth;
+                    self.pv_lines = helper.pv_lines.clone();
+                }
+            }
+        }
+        return best_move;
+    }
 
-        let bad_draw_score: i32 = -10;
+    // one thread's iterative deepening, only the main thread prints its progress
+    fn iterative_deepening(
+        &mut self,
+        mut copied_board: Board,
+        mut copied_state: GameState,
+        limits: SearchLimits,
+    ) -> Option<u16> {
+        let mut node_count: u64 = 0;
+        let mut best_move: Option<u16> = None;
+        let mut previous_best_move: u16 = 0;
 
-        let mut time_limit_ms: u128 = time_contrainsts.as_millis();
-        if time_limit_ms == 0 {
-            time_limit_ms = 1_000_000 * 1000; // 1_000_000 seconds
-        }
-        let max_depth_limit: u8 = max_depth + 1;
+        self.time_manager = limits.time_manager;
+        self.node_limit = limits.nodes.unwrap_or(u64::MAX);
+        let max_depth_limit: u8 = limits.depth + 1;
 
         let mut last_finished_depth: usize = 0;
         let mut depth_best_moves: [u16; 64] = [0; 64];
@@ -577,105 +1277,225 @@ impl Engine {
         let opponent_color: u16 = if self.side == 8 { 16 } else { 8 };
 
         let mut best_score_eval: i32 = 0;
-        let timer_start: Instant = Instant::now();
 
         'outer: for d in 1..=self.depth {
             if max_depth_limit == d {
                 break;
             }
+            // every other helper skips the even iterations, so that they do not all search the same
+            if self.thread_id % 2 == 1 && d > 1 && d % 2 == 0 {
+                continue;
+            }
             let depth_as_index: usize = d as usize;
 
             self.generate_pseudo_legal_moves(self.side, &copied_board, &copied_state, 0, false);
             let last_occupied: usize = self.move_lists[0].first_not_occupied;
-            self.how_much_searched.1 = last_occupied as f32;
 
             self.score_all_moves(0, last_occupied, &previous_best_move, &copied_board, false);
             let scores: &mut [i16; 192] = &mut self.move_scores[0];
             let moves: &mut [u16; 192] = &mut self.move_lists[0].pseudo_moves;
 
             Self::n_log_n_sort_moves(moves, scores, last_occupied);
-            let mut depth_best_score: i32 = -CHECKMATE_VALUE;
-            let mut depth_best_move: u16 = 0;
+            // aspiration window around the previous score, widened on every fail
+            let (mut window_alpha, mut window_beta, mut window_delta): (i32, i32, i32) = if d
+                >= ASPIRATION_MIN_DEPTH
+                && self.aspiration_windows
+                && self.multi_pv <= 1
+                && best_score_eval.abs() < MATE_SCORE_THRESHOLD
+            {
+                (
+                    best_score_eval - ASPIRATION_WINDOW,
+                    best_score_eval + ASPIRATION_WINDOW,
+                    ASPIRATION_WINDOW,
+                )
+            } else {
+                (-CHECKMATE_VALUE, CHECKMATE_VALUE, 0)
+            };
+            let exact_lines: usize = self.multi_pv.max(1);
+
+            let (
+                mut depth_best_score,
+                mut depth_best_move,
+                mut root_lines,
+                mut moves_searched,
+                mut total_moves,
+            ): (i32, u16, Vec<RootLine>, usize, usize);
+            loop {
+                depth_best_score = -CHECKMATE_VALUE;
+                depth_best_move = 0;
+                root_lines = Vec::with_capacity(last_occupied);
+                moves_searched = 0;
+                total_moves = last_occupied;
+                self.seldepth = 0;
+
+                for i in 0..last_occupied {
+                    let allegedly_best_move: u16 = self.move_lists[0].pseudo_moves[i];
+                    if !limits.search_moves.is_empty()
+                        && !limits
+                            .search_moves
+                            .contains(&without_check_flag(allegedly_best_move))
+                    {
+                        total_moves -= 1;
+                        continue;
+                    }
 
-            let mut moves_searched: usize = 0;
+                    self.played_piece_squares[0] =
+                        Some(Self::piece_square(&copied_board, allegedly_best_move));
+                    copied_board.perform_move(
+                        allegedly_best_move,
+                        &mut copied_state,
+                        self.side,
+                        &mut self.evaluation,
+                        &mut self.current_hash,
+                    );
 
-            let mut total_moves: usize = last_occupied;
+                    if copied_board.is_square_attacked(
+                        if self.side == 8 {
+                            copied_board.white_king_square
+                        } else {
+                            copied_board.black_king_square
+                        },
+                        opponent_color,
+                    ) {
+                        copied_board.cancel_move(
+                            &mut copied_state,
+                            self.side,
+                            &mut self.evaluation,
+                            &mut self.current_hash,
+                        );
+                        total_moves -= 1;
+                        continue;
+                    }
 
-            for i in 0..last_occupied {
-                let allegedly_best_move: u16 = self.move_lists[0].pseudo_moves[i];
+                    if self.thread_id == 0
+                        && self.time_manager.elapsed_ms() > CURRMOVE_INFO_DELAY_MS
+                    {
+                        println!(
+                            "info depth {d} currmove {} currmovenumber {}\r",
+                            move_to_uci(allegedly_best_move, self.chess960),
+                            moves_searched + 1
+                        );
+                    }
 
-                copied_board.perform_move(
-                    allegedly_best_move,
-                    &mut copied_state,
-                    self.side,
-                    &mut self.evaluation,
-                    &mut self.current_hash,
-                );
+                    let move_extension: u8 =
+                        Self::move_increment(&copied_board.cached_pieces, allegedly_best_move);
 
-                if copied_board.is_square_attacked(
-                    if self.side == 8 {
-                        copied_board.white_king_square
+                    // PVS: the best "multi_pv" moves so far set the bar, which the other
+                    // moves first have to clear with a null window
+                    let root_alpha: i32 = if root_lines.len() < exact_lines {
+                        window_alpha
                     } else {
-                        copied_board.black_king_square
-                    },
-                    opponent_color,
-                ) {
+                        let mut scores: Vec<i32> = root_lines.iter().map(|l| l.score).collect();
+                        scores.sort_unstable_by(|a, b| b.cmp(a));
+                        scores[exact_lines - 1].max(window_alpha)
+                    };
+                    let mut score: i32;
+                    if moves_searched < exact_lines {
+                        score = -self.negamax(
+                            &mut copied_board,
+                            d - 1 + move_extension,
+                            1,
+                            opponent_color,
+                            -window_beta,
+                            -root_alpha,
+                            &mut copied_state,
+                            &mut node_count,
+                            depth_as_index,
+                        );
+                    } else {
+                        score = -self.negamax(
+                            &mut copied_board,
+                            d - 1 + move_extension,
+                            1,
+                            opponent_color,
+                            -root_alpha - 1,
+                            -root_alpha,
+                            &mut copied_state,
+                            &mut node_count,
+                            depth_as_index,
+                        );
+                        if score > root_alpha && score < window_beta {
+                            score = -self.negamax(
+                                &mut copied_board,
+                                d - 1 + move_extension,
+                                1,
+                                opponent_color,
+                                -window_beta,
+                                -root_alpha,
+                                &mut copied_state,
+                                &mut node_count,
+                                depth_as_index,
+                            );
+                        }
+                    }
+
+                    copied_state.whose_turn = self.side;
+
+                    if score.abs() == TIMEOUT_RETURN {
+                        if last_finished_depth == 0 {
+                            // stopped before the first iteration ended, any legal move is better than none
+                            previous_best_move = if depth_best_move != 0 {
+                                depth_best_move
+                            } else {
+                                allegedly_best_move
+                            };
+                            depth_best_moves[0] = previous_best_move;
+                        }
+                        break 'outer;
+                    }
+
+                    moves_searched += 1;
+
                     copied_board.cancel_move(
                         &mut copied_state,
                         self.side,
                         &mut self.evaluation,
                         &mut self.current_hash,
                     );
-                    total_moves -= 1;
-                    continue;
-                }
-
-                self.how_much_searched.0 += 1.;
-                let move_extension: u8 =
-                    Self::move_increment(&copied_board.cached_pieces, allegedly_best_move);
 
-                let mut score: i32 = -self.negamax(
-                    &mut copied_board,
-                    d - 1 + move_extension,
-                    1,
-                    opponent_color,
-                    -CHECKMATE_VALUE,
-                    CHECKMATE_VALUE,
-                    &mut copied_state,
-                    &mut node_count,
-                    &timer_start,
-                    &time_limit_ms,
-                    depth_as_index,
-                );
-
-                copied_state.whose_turn = self.side;
+                    let flag: u8 = if score >= window_beta {
+                        1
+                    } else if score <= root_alpha {
+                        2
+                    } else {
+                        0
+                    };
+                    let mut line: Vec<u16> = vec![allegedly_best_move];
+                    line.extend_from_slice(&self.pv_table[1][1..self.pv_length[1]]);
+                    if flag == 0 {
+                        self.extend_pv_from_tt(&copied_board, &copied_state, &mut line, d as usize);
+                    }
+                    root_lines.push(RootLine { score, flag, line });
 
-                if score.abs() == TIMEOUT_RETURN {
-                    break 'outer;
+                    if score > depth_best_score || depth_best_move == 0 {
+                        depth_best_score = score;
+                        depth_best_move = allegedly_best_move;
+                    }
                 }
 
-                moves_searched += 1;
-
-                if copied_state.is_repetition(self.current_hash)
-                    || copied_state.fifty_moves_rule_counter >= 98
-                {
-                    score = if score <= bad_draw_score {
-                        0
-                    } else {
-                        bad_draw_score
+                // a fail outside the window only tells a bound, it is reported and searched again
+                let failed_low: bool =
+                    depth_best_score <= window_alpha && window_alpha > -CHECKMATE_VALUE;
+                let failed_high: bool =
+                    depth_best_score >= window_beta && window_beta < CHECKMATE_VALUE;
+                if !failed_low && !failed_high {
+                    break;
+                }
+                if let Some(best_line) = root_lines.iter().find(|l| l.line[0] == depth_best_move) {
+                    let bound_line: RootLine = RootLine {
+                        score: depth_best_score,
+                        flag: if failed_high { 1 } else { 2 },
+                        line: best_line.line.clone(),
                     };
+                    self.print_search_info(d, node_count, &[bound_line]);
                 }
-
-                copied_board.cancel_move(
-                    &mut copied_state,
-                    self.side,
-                    &mut self.evaluation,
-                    &mut self.current_hash,
-                );
-
-                if score > depth_best_score || depth_best_move == 0 {
-                    depth_best_score = score;
-                    depth_best_move = allegedly_best_move;
+                window_delta *= 2;
+                if window_delta > ASPIRATION_MAX_WINDOW {
+                    (window_alpha, window_beta) = (-CHECKMATE_VALUE, CHECKMATE_VALUE);
+                } else if failed_low {
+                    window_alpha = (depth_best_score - window_delta).max(-CHECKMATE_VALUE);
+                } else {
+                    window_beta = (depth_best_score + window_delta).min(CHECKMATE_VALUE);
                 }
             }
             if moves_searched == total_moves || depth_best_score <= best_score_eval {
@@ -684,47 +1504,43 @@ impl Engine {
                 previous_best_move = depth_best_move;
                 depth_best_moves[last_finished_depth] = previous_best_move;
                 last_finished_depth += 1;
-                let (from, to, flag) = (
-                    INDICES_TO_COORDS
-                        .get(&from_square(previous_best_move))
-                        .unwrap()
-                        .clone(),
-                    INDICES_TO_COORDS
-                        .get(&(to_square(previous_best_move) as u8))
-                        .unwrap()
-                        .clone(),
-                    match (previous_best_move & MARK_MASK) >> MARK_SHIFT {
-                        3 | 10 => "n",
-                        4 | 11 => "b",
-                        5 | 12 => "r",
-                        6 | 13 => "q",
-                        _ => "",
-                    },
-                );
-                let uci_move: String = format!("{}{}{}", from, to, flag);
-                println!(
-                    "info depth {d} score cp {depth_best_score} time {} nodes {node_count} pv {uci_move}\r",
-                    timer_start.elapsed().as_millis(),
-                );
+                self.completed_depth = d;
+                // the best "multi_pv" moves have exact scores, the rest are upper bounds no
+                // better than them. the sort is stable: among equal scores the first searched
+                // move stays first, as it is the one whose score is exact
+                root_lines.sort_by(|a, b| b.score.cmp(&a.score));
+                root_lines.truncate(exact_lines);
+                self.pv_lines = root_lines;
+                self.print_search_info(d, node_count, &self.pv_lines);
+                self.time_manager
+                    .update(previous_best_move, depth_best_score);
+                if self.is_iteration_time_over() {
+                    break;
+                }
+                if let Some(mate) = limits.mate
+                    && depth_best_score >= MATE_SCORE_THRESHOLD
+                    && (CHECKMATE_VALUE - depth_best_score + 1) / 2 <= mate as i32
+                {
+                    break;
+                }
                 continue;
             }
             break;
         }
-        if *depth_best_moves
-            .iter()
-            .take_while(|&&m| m != 0)
-            .last()
-            .unwrap()
-            != 0
-        {
+        if depth_best_moves[0] != 0 {
             best_move = Some(previous_best_move);
         }
 
-        println!("info string HCE eval: {best_score_eval}\r");
-        println!("info string nodes: {node_count}\r");
+        if self.thread_id == 0 {
+            println!("info string HCE eval: {best_score_eval}\r");
+            println!("info string nodes: {node_count}\r");
+        }
+        self.searched_nodes = node_count;
         return best_move;
     }
 
+    // searches "moves_amount" lines to "max_depth" and keeps the moves whose score
+    // is within "good_move_margin" of the best one
     pub fn find_multiple_moves(
         &mut self,
         board: &Board,
@@ -732,167 +1548,146 @@ impl Engine {
         max_depth: u8,
         moves_amount: usize,
         needed_color: u16,
-        first_move: bool,
+        good_move_margin: i32,
     ) -> Vec<Option<u16>> {
         assert_eq!(needed_color, self.side);
-        if moves_amount > 5 {
-            panic!("cannot give more than 5 best moves");
-        }
-        let mut node_count: u64 = 0;
-
-        let mut best_moves: Vec<Option<u16>> = Vec::new();
-        let mut all_scores: Vec<Option<i32>> = Vec::new();
-
-        let mut copied_board: Board = board.clone();
-        let mut copied_state: GameState = state.clone();
-        copied_state.whose_turn = self.side as u16;
+        let (multi_pv, own_book): (usize, bool) = (self.multi_pv, self.own_book);
+        self.multi_pv = moves_amount;
+        self.own_book = false;
 
-        self.prepare_before_search(&mut copied_board, &mut copied_state);
-
-        let mut previous_best_move: u16 = 0;
-
-        let bad_draw_score: i32 = -50;
-
-        let time_limit_ms: u128 = 1_000_000 * 1000; // 1_000_000 seconds
-        let max_depth_limit: u8 = max_depth + 1;
-
-        let mut last_finished_depth: usize = 0;
-        let mut depth_best_moves: [u16; 64] = [0; 64];
+        self.find_best_move(board, state, SearchLimits::depth(max_depth));
 
-        let opponent_color: u16 = if self.side == 8 { 16 } else { 8 };
+        self.multi_pv = multi_pv;
+        self.own_book = own_book;
 
-        let mut best_score_eval: i32 = 0;
-        let timer_start: Instant = Instant::now();
+        let Some(best_score) = self.pv_lines.first().map(|l| l.score) else {
+            return Vec::new();
+        };
+        return self
+            .pv_lines
+            .iter()
+            .filter(|l| l.score + good_move_margin >= best_score)
+            .map(|l| Some(l.line[0]))
+            .collect();
+    }
 
-        'outer: for d in 1..=self.depth {
-            if max_depth_limit == d {
+    #[inline(always)]
+    // an exact TT hit returns without filling the PV below it, so the line is continued
+    // with the TT best moves. they are checked to be legal, as a hash collision may give any move
+    fn extend_pv_from_tt(
+        &self,
+        board: &Board,
+        state: &GameState,
+        line: &mut Vec<u16>,
+        max_length: usize,
+    ) -> () {
+        let (mut board, mut state): (Board, GameState) = (board.clone(), state.clone());
+        let (mut color, mut evaluation, mut hash): (u16, i32, u64) =
+            (self.side, self.evaluation, self.current_hash);
+        let mut seen_hashes: Vec<u64> = vec![hash];
+        for &m in line.iter() {
+            board.perform_move(m, &mut state, color, &mut evaluation, &mut hash);
+            color = if color == 8 { 16 } else { 8 };
+            seen_hashes.push(hash);
+        }
+        while line.len() < max_length {
+            let Some(entry) = self.transposition_table.get_entry(&hash, 0) else {
+                break;
+            };
+            let mut moves: MoveList = MoveList {
+                pseudo_moves: [0; 192],
+                first_not_occupied: 0,
+            };
+            board.pawn_moves(&state, color, &mut moves, false);
+            board.knight_moves(color, &mut moves, &state, false);
+            board.bishop_moves(color, &mut moves, &state, false);
+            board.queen_moves(color, &mut moves, &state, false);
+            board.rook_moves(color, &mut moves, &state, false);
+            board.king_moves(&state, color, &mut moves, false);
+            if entry.best_move == NULL_MOVE
+                || !moves.pseudo_moves[..moves.first_not_occupied].contains(&entry.best_move)
+            {
                 break;
             }
-            let depth_as_index: usize = d as usize;
-
-            self.generate_pseudo_legal_moves(self.side, &copied_board, &copied_state, 0, false);
-            let last_occupied: usize = self.move_lists[0].first_not_occupied;
-            self.how_much_searched.1 = last_occupied as f32;
-
-            self.score_all_moves(0, last_occupied, &previous_best_move, &copied_board, false);
-            let scores: &mut [i16; 192] = &mut self.move_scores[0];
-            let moves: &mut [u16; 192] = &mut self.move_lists[0].pseudo_moves;
-
-            Self::n_log_n_sort_moves(moves, scores, last_occupied);
-            let mut depth_best_score: i32 = -CHECKMATE_VALUE;
-            let mut depth_best_move: u16 = 0;
-
-            let mut moves_searched: usize = 0;
-
-            let mut total_moves: usize = last_occupied;
-
-            for i in 0..last_occupied {
-                let allegedly_best_move: u16 = self.move_lists[0].pseudo_moves[i];
-
-                copied_board.perform_move(
-                    allegedly_best_move,
-                    &mut copied_state,
-                    self.side,
-                    &mut self.evaluation,
-                    &mut self.current_hash,
-                );
-
-                if copied_board.is_square_attacked(
-                    if self.side == 8 {
-                        copied_board.white_king_square
-                    } else {
-                        copied_board.black_king_square
-                    },
-                    opponent_color,
-                ) {
-                    copied_board.cancel_move(
-                        &mut copied_state,
-                        self.side,
-                        &mut self.evaluation,
-                        &mut self.current_hash,
-                    );
-                    total_moves -= 1;
-                    continue;
-                }
-
-                self.how_much_searched.0 += 1.;
-                let move_extension: u8 =
-                    Self::move_increment(&copied_board.cached_pieces, allegedly_best_move);
-
-                let mut score: i32 = -self.negamax(
-                    &mut copied_board,
-                    d - 1 + move_extension,
-                    1,
-                    opponent_color,
-                    -CHECKMATE_VALUE,
-                    CHECKMATE_VALUE,
-                    &mut copied_state,
-                    &mut node_count,
-                    &timer_start,
-                    &time_limit_ms,
-                    depth_as_index,
-                );
-
-                if d >= max_depth {
-                    all_scores.push(Some(score));
-                    best_moves.push(Some(allegedly_best_move));
-                }
-
-                copied_state.whose_turn = self.side;
-
-                if score == TIMEOUT_RETURN || score == -TIMEOUT_RETURN {
-                    break 'outer;
-                }
-
-                moves_searched += 1;
-
-                if copied_state.is_repetition(self.current_hash)
-                    || copied_state.fifty_moves_rule_counter >= 98
-                {
-                    score = if score <= bad_draw_score {
-                        0
-                    } else {
-                        bad_draw_score
-                    };
-                }
-
-                copied_board.cancel_move(
-                    &mut copied_state,
-                    self.side,
-                    &mut self.evaluation,
-                    &mut self.current_hash,
-                );
-
-                if score > depth_best_score || depth_best_move == 0 {
-                    depth_best_score = score;
-                    depth_best_move = allegedly_best_move;
-                }
-            }
-            if moves_searched == total_moves || depth_best_score <= best_score_eval {
-                // do not discard best move if it's better than what we already have
-                best_score_eval = depth_best_score;
-                previous_best_move = depth_best_move;
-                depth_best_moves[last_finished_depth] = previous_best_move;
-                last_finished_depth += 1;
-                continue;
+            board.perform_move(
+                entry.best_move,
+                &mut state,
+                color,
+                &mut evaluation,
+                &mut hash,
+            );
+            let king_square: u8 = if color == 8 {
+                board.white_king_square
+            } else {
+                board.black_king_square
+            };
+            color = if color == 8 { 16 } else { 8 };
+            // a repetition would go on forever
+            if board.is_square_attacked(king_square, color) || seen_hashes.contains(&hash) {
+                break;
             }
-            break;
+            line.push(entry.best_move);
+            seen_hashes.push(hash);
         }
+    }
 
-        let best_indices: Vec<usize> =
-            Self::find_best_scores(all_scores.clone(), moves_amount, first_move);
+    fn update_pv(&mut self, ply: usize, m: u16) -> () {
+        let child_pv_length: usize = self.pv_length[ply + 1].max(ply + 1);
+        self.pv_table[ply][ply] = m;
+        for i in (ply + 1)..child_pv_length {
+            self.pv_table[ply][i] = self.pv_table[ply + 1][i];
+        }
+        self.pv_length[ply] = child_pv_length;
+    }
 
-        return best_moves
-            .into_iter()
-            .enumerate()
-            .filter_map(|(i, mv)| {
-                if best_indices.contains(&i) {
-                    Some(mv)
-                } else {
-                    None
-                }
-            })
-            .collect();
+    fn print_search_info(&self, depth: u8, node_count: u64, lines: &[RootLine]) -> () {
+        if self.thread_id != 0 {
+            return;
+        }
+        let node_count: u64 = node_count + self.signals.helper_nodes.load(Ordering::Relaxed);
+        let elapsed_ms: u128 = self.time_manager.elapsed_ms();
+        let nps: u128 = node_count as u128 * 1000 / elapsed_ms.max(1);
+        let hashfull: u64 = self.transposition_table.hashfull();
+        for (i, root_line) in lines.iter().enumerate() {
+            let pv: Vec<String> = root_line
+                .line
+                .iter()
+                .map(|m| move_to_uci(*m, self.chess960))
+                .collect();
+            let bound: &str = match root_line.flag {
+                1 => " lowerbound",
+                2 => " upperbound",
+                _ => "",
+            };
+            println!(
+                "info depth {depth} seldepth {} multipv {} score {}{bound} nodes {node_count} nps {nps} hashfull {hashfull} time {elapsed_ms} pv {}\r",
+                self.seldepth,
+                i + 1,
+                score_to_uci(root_line.score),
+                pv.join(" "),
+            );
+        }
+    }
+
+    // forgets everything learned in the previous games
+    pub fn new_game(&mut self) -> () {
+        self.history_heuristics = [0; 4096];
+        self.killer_moves = [[None; 2]; 128];
+        self.counter_moves = [0; PIECE_SQUARES];
+        self.capture_history = [0; PIECE_SQUARES * 6];
+        self.continuation_history = [
+            Self::empty_continuation_history(),
+            Self::empty_continuation_history(),
+        ];
+        self.move_lists = [MoveList {
+            pseudo_moves: [0; 192],
+            first_not_occupied: 0,
+        }; 128];
+        self.move_scores = [[0; 192]; 128];
+        self.current_hash = 0;
+        self.evaluation = 0;
+        self.nodes_since_last_check = 0;
+        self.transposition_table.clear();
     }
 
     fn prepare_before_search(&mut self, board: &mut Board, state: &mut GameState) -> () {
@@ -901,6 +1696,7 @@ impl Engine {
             *history_score = (*history_score * 6) / 9; // more aggressive decay
         }
         self.killer_moves = [[None; 2]; 128];
+        self.null_move_min_ply = 0;
         self.move_lists = [MoveList {
             pseudo_moves: [0; 192],
             first_not_occupied: 0,
@@ -908,44 +1704,23 @@ impl Engine {
         self.move_scores = [[0; 192]; 128];
         self.current_hash = 0;
         self.evaluation = 0;
+        self.pv_lines.clear();
+        self.completed_depth = 0;
 
-        self.transposition_table.hits = 0;
-        self.transposition_table.collisions = 0;
-        self.transposition_table.replacements = 0;
-
-        self.how_much_searched.0 = 0.;
-        self.how_much_searched.1 = 0.;
-
-        self.current_hash = Self::rebuild_hash(board, self.side);
+        self.current_hash = Self::rebuild_hash(board, state, self.side);
+        Self::update_king_heuristics(board);
         self.evaluate(board);
         board.calculate_check_restrictions(state, state.whose_turn);
     }
 
-    pub fn rebuild_hash(board: &Board, side: u16) -> u64 {
+    pub fn rebuild_hash(board: &Board, state: &GameState, side: u16) -> u64 {
         // calculate the hash of the position in the beginning
-        let (
-            mut white_queens_amount,
-            mut black_queens_amount,
-            mut white_pieces_left,
-            mut black_pieces_left,
-        ) = (0, 0, 0, 0);
         let mut board_hash = 0;
         for (i, piece) in board.cached_pieces.iter().enumerate() {
             let piece: u16 = *piece;
             if piece != 0 {
                 let zobrist_index: usize = (piece as usize - 1) * 64 + i;
                 board_hash ^= ZOBRIST_HASH_TABLE[zobrist_index];
-                match piece {
-                    WHITE_QUEEN_U16 => white_queens_amount += 1,
-                    BLACK_QUEEN_U16 => black_queens_amount += 1,
-                    WHITE_KING_U16 | BLACK_KING_U16 => continue, // don't count kings, they self negate each other
-                    _ => (),
-                };
-                if piece < 7 {
-                    white_pieces_left += 1;
-                } else {
-                    black_pieces_left += 1;
-                }
             }
         }
         board_hash ^= if side == 8 {
@@ -953,36 +1728,140 @@ impl Engine {
         } else {
             BLACK_ZOBRIST_KEY
         };
-        let piece_heuristic_table: *mut [[i32; 64]; 12] = &raw mut HEURISTICS_TABLE;
-        unsafe {
-            if white_queens_amount == 0 && white_pieces_left < 8 {
-                (*piece_heuristic_table)[5] = ENDGAME_WHITE_KING_HEURISTICS;
-            } else {
-                (*piece_heuristic_table)[5] = WHITE_KING_HEURISTICS;
-            }
-            if black_queens_amount == 0 && black_pieces_left < 8 {
-                (*piece_heuristic_table)[11] = ENDGAME_BLACK_KING_HEURISTICS;
+        board_hash ^= state.castling_and_en_passant_hash();
+        return board_hash;
+    }
+
+    // internal iterative reduction: a node without a TT move is ordered blindly, so it gets a
+    // shallower search, which leaves a TT move for the next iteration. a singular search keeps
+    // its depth, its node already has the TT move it is excluding
+    #[inline(always)]
+    pub fn iir_reduction(depth: u8, tt_move: u16, excluded_move: u16) -> u8 {
+        return (depth >= IIR_MIN_DEPTH && tt_move == 0 && excluded_move == 0) as u8;
+    }
+
+    #[inline(always)]
+    fn has_non_pawn_material(board: &Board, color: u16) -> bool {
+        let first_piece: usize = if color == 8 { 1 } else { 7 };
+        return board.bitboards[first_piece..first_piece + 4]
+            .iter()
+            .any(|pieces| *pieces != 0);
+    }
+
+    // the incrementally updated hash has to match the one built from scratch
+    #[inline(always)]
+    fn debug_assert_hash(&self, board: &Board, state: &GameState, color: u16) -> () {
+        debug_assert_eq!(
+            self.current_hash,
+            Self::rebuild_hash(board, state, color),
+            "incremental hash differs from the rebuilt one in {}",
+            board_to_fen(board, state, &(color as u8))
+        );
+    }
+
+    // the kings switch to their endgame tables when the side has no queen and few pieces left
+    pub fn update_king_heuristics(board: &mut Board) -> () {
+        let (
+            mut white_queens_amount,
+            mut black_queens_amount,
+            mut white_pieces_left,
+            mut black_pieces_left,
+        ) = (0, 0, 0, 0);
+        for piece in board.cached_pieces {
+            match piece {
+                0 | WHITE_KING_U16 | BLACK_KING_U16 => continue, // don't count kings, they self negate each other
+                WHITE_QUEEN_U16 => white_queens_amount += 1,
+                BLACK_QUEEN_U16 => black_queens_amount += 1,
+                _ => (),
+            };
+            if piece < 7 {
+                white_pieces_left += 1;
             } else {
-                (*piece_heuristic_table)[11] = BLACK_KING_HEURISTICS;
+                black_pieces_left += 1;
             }
         }
-        return board_hash;
+        board.king_phases = 0;
+        if white_queens_amount == 0 && white_pieces_left < 8 {
+            board.king_phases |= WHITE_KING_ENDGAME;
+        }
+        if black_queens_amount == 0 && black_pieces_left < 8 {
+            board.king_phases |= BLACK_KING_ENDGAME;
+        }
+    }
+
+    // every draw is scored from Ferrous' point of view, then turned to the side to move
+    #[inline(always)]
+    fn draw_score(&self, color: u16) -> i32 {
+        return if color == self.side {
+            -self.contempt
+        } else {
+            self.contempt
+        };
+    }
+
+    #[inline(always)]
+    fn is_time_over(&self) -> bool {
+        let signals: &SearchSignals = &self.signals;
+        if self.is_stopped() {
+            return true;
+        }
+        if signals.infinite.load(Ordering::Relaxed) || signals.pondering.load(Ordering::Relaxed) {
+            return false;
+        }
+        return self.clock_time_ms() >= self.time_manager.hard_limit_ms;
+    }
+
+    #[inline(always)]
+    fn is_stopped(&self) -> bool {
+        return self.signals.stop.load(Ordering::Relaxed)
+            || (self.thread_id != 0 && self.signals.helpers_stop.load(Ordering::Relaxed));
+    }
+
+    // "go nodes" counts every thread, the others as far as they have reported their nodes
+    #[inline(always)]
+    fn is_node_limit_reached(&self, node_count: u64) -> bool {
+        if self.node_limit == u64::MAX {
+            return false;
+        }
+        let signals: &SearchSignals = &self.signals;
+        let helper_nodes: u64 = signals.helper_nodes.load(Ordering::Relaxed);
+        if self.thread_id == 0 {
+            return node_count + helper_nodes >= self.node_limit;
+        }
+        // the helper's own reported nodes are in "helper_nodes" already
+        return signals.main_nodes.load(Ordering::Relaxed)
+            + helper_nodes
+            + self.nodes_since_last_check
+            >= self.node_limit;
     }
 
     #[inline(always)]
-    fn proceed_search(&self, depth: u8) -> bool {
-        let depth_percent: f32 = match depth {
-            1..=4 => 0.10,
-            5..=8 => 0.05,
-            _ => 0.02,
+    fn report_nodes(&self) -> () {
+        let nodes: &AtomicU64 = if self.thread_id == 0 {
+            &self.signals.main_nodes
+        } else {
+            &self.signals.helper_nodes
         };
+        nodes.fetch_add(TIME_CHECK_NODES_OFFSET, Ordering::Relaxed);
+    }
 
-        return self.percent_finished() < depth_percent;
+    // checked between iterations, a new one is not worth starting after the soft limit
+    #[inline(always)]
+    fn is_iteration_time_over(&self) -> bool {
+        let signals: &SearchSignals = &self.signals;
+        if signals.infinite.load(Ordering::Relaxed) || signals.pondering.load(Ordering::Relaxed) {
+            return false;
+        }
+        return self.clock_time_ms() >= self.time_manager.soft_limit_ms;
     }
 
+    // while pondering the clock was not ours, it only starts on "ponderhit"
     #[inline(always)]
-    fn percent_finished(&self) -> f32 {
-        return self.how_much_searched.0 / self.how_much_searched.1;
+    pub fn clock_time_ms(&self) -> u128 {
+        return self
+            .time_manager
+            .elapsed_ms()
+            .saturating_sub(self.signals.ponderhit_ms.load(Ordering::Relaxed) as u128);
     }
 
     #[inline(always)]
@@ -1005,57 +1884,7 @@ impl Engine {
 
     #[inline(always)]
     fn is_quiet(board: &[u16; 64], m: u16) -> bool {
-        return board[to_square(m) as usize] == 0 && ((m & MARK_MASK) >> MARK_SHIFT) < 3;
-    }
-
-    fn find_best_scores(
-        scores: Vec<Option<i32>>,
-        scores_amount: usize,
-        first_move: bool,
-    ) -> Vec<usize> {
-        let mut best_indices: Vec<usize> = Vec::with_capacity(scores_amount);
-        let mut remaining_indices: Vec<usize> = (0..scores.len()).collect();
-
-        let good_move_margin: i32 = if first_move { 50 } else { 10 };
-
-        for _ in 0..scores_amount.min(remaining_indices.len()) {
-            let (mut current_best_score, mut current_best_position) = (-CHECKMATE_VALUE, 0);
-
-            for (pos, &idx) in remaining_indices.iter().enumerate() {
-                if let Some(score) = scores[idx] {
-                    if score > current_best_score {
-                        current_best_score = score;
-                        current_best_position = pos;
-                    }
-                }
-            }
-
-            let best_original_index: usize = remaining_indices.remove(current_best_position);
-            best_indices.push(best_original_index);
-        }
-        let best_scores: Vec<i32> = scores
-            .iter()
-            .enumerate()
-            .filter_map(|(i, &m)| if best_indices.contains(&i) { m } else { None })
-            .collect();
-
-        let max_score: i32 = *best_scores.iter().max().unwrap();
-        let mut bad_scores_indices: Vec<usize> = Vec::new();
-        for (idx, score) in best_scores.iter().enumerate() {
-            if score + good_move_margin < max_score {
-                bad_scores_indices.push(idx);
-            }
-        }
-
-        return best_indices
-            .into_iter()
-            .filter_map(|movelist_index: usize| {
-                if bad_scores_indices.contains(&movelist_index) {
-                    None
-                } else {
-                    Some(movelist_index)
-                }
-            })
-            .collect();
+        let flag: u16 = (m & MARK_MASK) >> MARK_SHIFT;
+        return (board[to_square(m) as usize] == 0 || flag == 1) && flag < 3;
     }
 }
@@ -0,0 +1,75 @@
+#[allow(unused_imports)]
+use crate::{
+    board::Board,
+    converters::{
+        fen_converter::{board_to_fen, fen_to_board},
+        move_converter::{move_to_uci, uci_to_move},
+    },
+    gamestate::GameState,
+    search::Engine,
+};
+
+#[cfg(test)]
+fn perft_nodes(fen: &str, depth: usize) -> u64 {
+    let (mut board, mut state) = fen_to_board(fen);
+    let color: u16 = state.whose_turn;
+    let mut engine: Engine = Engine::new(color, depth as u8);
+    return engine
+        .perft_test(depth, &mut board, &mut state, color)
+        .total_nodes;
+}
+
+#[test]
+fn chess960_perft_test1() -> () {
+    assert_eq!(
+        perft_nodes(
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+            4
+        ),
+        326672
+    );
+}
+
+#[test]
+fn chess960_perft_test2() -> () {
+    // the rook on b1 shields the king from the queen, so the long castling is illegal
+    assert_eq!(perft_nodes("4k3/8/8/8/8/8/8/qRK4R w HB - 0 1", 4), 106265);
+    // the kings and the rooks castle without leaving their squares or by swapping them
+    assert_eq!(
+        perft_nodes("rkr5/pppppppp/8/8/8/8/PPPPPPPP/RKR5 w CAca - 0 1", 4),
+        237384
+    );
+}
+
+#[test]
+fn chess960_castling_test1() -> () {
+    let fen: &str = "1rk2r2/pp3ppp/3q4/8/8/3Q4/PP3PPP/1RK2R2 w FBfb - 0 1";
+    let (mut board, mut state) = fen_to_board(fen);
+    assert_eq!(board_to_fen(&board, &state, &8), fen);
+
+    let m: u16 = uci_to_move(&board, &state, "c1f1").unwrap();
+    assert_eq!(move_to_uci(m, true), "c1f1");
+    assert_eq!(move_to_uci(m, false), "c1g1");
+
+    let (board_before, state_before): (Board, GameState) = (board.clone(), state.clone());
+    let mut engine: Engine = Engine::new(8, 1);
+    engine.evaluate(&board);
+    let mut hash: u64 = Engine::rebuild_hash(&board, &state, 8);
+    let (evaluation_before, hash_before): (i32, u64) = (engine.evaluation, hash);
+
+    board.perform_move(m, &mut state, 8, &mut engine.evaluation, &mut hash);
+    assert_eq!(
+        board_to_fen(&board, &state, &16),
+        "1rk2r2/pp3ppp/3q4/8/8/3Q4/PP3PPP/1R3RK1 b fb - 1 1"
+    );
+    assert_eq!(hash, Engine::rebuild_hash(&board, &state, 16));
+    let evaluation_after: i32 = engine.evaluation;
+    engine.evaluate(&board);
+    assert_eq!(evaluation_after, engine.evaluation);
+
+    engine.evaluation = evaluation_after;
+    board.cancel_move(&mut state, 8, &mut engine.evaluation, &mut hash);
+    assert_eq!(board, board_before);
+    assert_eq!(state, state_before);
+    assert_eq!((engine.evaluation, hash), (evaluation_before, hash_before));
+}
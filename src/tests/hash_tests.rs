@@ -0,0 +1,96 @@
+#[allow(unused_imports)]
+use crate::{
+    converters::fen_converter::fen_to_board,
+    converters::move_converter::uci_to_move,
+    gamestate::GameState,
+    search::{CHECKMATE_VALUE, Engine},
+    transposition::{TTEntry, TranspositionTable},
+};
+
+#[cfg(test)]
+fn fen_hash(fen: &str) -> u64 {
+    let (board, state) = fen_to_board(fen);
+    return Engine::rebuild_hash(&board, &state, state.whose_turn);
+}
+
+#[test]
+fn hash_test1() -> () {
+    // the same pieces with different castling rights and en passant squares
+    let fen: &str = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+    assert_ne!(
+        fen_hash(fen),
+        fen_hash("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3")
+    );
+    assert_ne!(
+        fen_hash(fen),
+        fen_hash("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w Kkq f6 0 3")
+    );
+
+    // the incremental updates agree with the rebuilt hash after castling, en passant and back
+    let (mut board, mut state) = fen_to_board(fen);
+    let mut hash: u64 = fen_hash(fen);
+    for (uci_move, color) in [("e5f6", 8), ("e8d7", 16), ("e1e2", 8)] {
+        let m: u16 = uci_to_move(&board, &state, uci_move).unwrap();
+        board.perform_move(m, &mut state, color, &mut 0, &mut hash);
+        state.whose_turn = if color == 8 { 16 } else { 8 };
+        assert_eq!(hash, Engine::rebuild_hash(&board, &state, state.whose_turn));
+    }
+    for color in [8, 16, 8] {
+        board.cancel_move(&mut state, color, &mut 0, &mut hash);
+    }
+    assert_eq!(hash, fen_hash(fen));
+}
+
+#[test]
+fn null_move_test1() -> () {
+    let fen: &str = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+    let (board, mut state) = fen_to_board(fen);
+    let state_before: GameState = state.clone();
+    let mut hash: u64 = fen_hash(fen);
+
+    // passing the turn drops the en passant square
+    board.perform_null_move(&mut state, 8, &mut hash);
+    assert_eq!(state.en_passant_target, None);
+    assert_eq!(
+        hash,
+        fen_hash("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3")
+    );
+
+    board.cancel_null_move(&mut state, &mut hash);
+    assert_eq!(hash, fen_hash(fen));
+    assert_eq!(state, state_before);
+}
+
+#[test]
+fn transposition_test1() -> () {
+    let table: TranspositionTable = TranspositionTable::new(1);
+    let hash: u64 = 0x9E37_79B9_7F4A_7C15;
+    table.record_entry(
+        &hash,
+        TTEntry {
+            hash,
+            score: -CHECKMATE_VALUE + 7, // mated in 7 plies from here, 3 plies away from the root
+            depth: 12,
+            flag: 2,
+            best_move: u16::MAX,
+        },
+        3,
+    );
+    // the packed entry reads back whole, with the mate distance from the root it is read at
+    let entry: TTEntry = table.get_entry(&hash, 5).unwrap();
+    assert_eq!(
+        (entry.score, entry.depth, entry.flag, entry.best_move),
+        (-CHECKMATE_VALUE + 9, 12, 2, u16::MAX)
+    );
+    assert!(table.get_entry(&(hash ^ 1), 5).is_none());
+
+    table.clear();
+    assert!(table.get_entry(&hash, 5).is_none());
+}
+
+#[test]
+fn transposition_test2() -> () {
+    // a size no machine has is refused instead of aborting the process
+    assert!(TranspositionTable::try_new(1 << 40).is_err());
+    assert!(TranspositionTable::try_new(1).is_ok());
+}
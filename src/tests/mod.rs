@@ -1,4 +1,10 @@
 mod check_tests;
+mod chess960_tests;
+mod hash_tests;
 mod movegen_flags_tests;
 pub mod perft;
 mod profiling;
+mod search_tests;
+mod see_tests;
+mod time_management_tests;
+mod uci_tests;
@@ -1,15 +1,10 @@
 #[allow(unused)]
-use crate::{constants::attacks::*, converters::fen_converter::fen_to_board, search::Engine};
+use crate::{converters::fen_converter::fen_to_board, search::Engine};
 #[allow(unused)]
 use std::time::Duration;
 
 #[test]
 fn move_order_test1() -> () {
-    initialize_sliding_attack_tables();
-    compute_all_rays();
-    compute_all_rays_from();
-    compute_all_lines();
-    compute_mvvlva();
     let (mut board, mut state) =
         fen_to_board("1r4k1/p1pqb1p1/2n4p/4n3/3p4/2N1BP2/PPQ2PKP/3RR3 w - - 0 20");
     board.total_occupancy();
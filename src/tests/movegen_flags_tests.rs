@@ -1,16 +1,10 @@
 #[allow(unused)]
 use crate::{
-    board_geometry_templates::TO_SHIFT, constants::attacks::*,
-    converters::fen_converter::fen_to_board, moves::MoveList,
+    board_geometry_templates::TO_SHIFT, converters::fen_converter::fen_to_board, moves::MoveList,
 };
 
 #[test]
 fn direct_check_test1() -> () {
-    initialize_sliding_attack_tables();
-    compute_all_rays();
-    compute_all_rays_from();
-    compute_all_lines();
-
     let (mut board, mut state) =
         fen_to_board("r3kbnr/ppp2ppp/2n5/3q4/3P2b1/5N2/PP3PPP/RNBQKB1R w KQkq - 1 7");
 
@@ -25,11 +19,6 @@ fn direct_check_test1() -> () {
 }
 #[test]
 fn direct_check_test2() -> () {
-    initialize_sliding_attack_tables();
-    compute_all_rays();
-    compute_all_rays_from();
-    compute_all_lines();
-
     let (mut board, mut state) =
         fen_to_board("rnbqkbnr/pp2pppp/2p5/1B1p4/4P3/8/PPPP1PPP/RNBQK1NR w KQkq - 0 3");
 
@@ -45,11 +34,6 @@ fn direct_check_test2() -> () {
 }
 #[test]
 fn direct_check_test3() -> () {
-    initialize_sliding_attack_tables();
-    compute_all_rays();
-    compute_all_rays_from();
-    compute_all_lines();
-
     let (mut board, mut state) =
         fen_to_board("r1b1k2r/pp2b2p/1R6/1N6/3pB3/7P/PPP1N1P1/R1B3K1 b kq - 0 20");
 
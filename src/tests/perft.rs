@@ -2,7 +2,7 @@
 use crate::{
     board::Board,
     board_geometry_templates::{MARK_MASK, MARK_SHIFT, from_square, to_square},
-    constants::attacks::*,
+    constants::attacks::INDICES_TO_COORDS,
     converters::fen_converter::fen_to_board,
     gamestate::GameState,
     moves::MoveList,
@@ -207,11 +207,6 @@ impl Engine {
 }
 
 pub fn run_perft(mut board: Board, mut state: GameState, depth: u8) -> () {
-    initialize_sliding_attack_tables();
-    compute_all_rays();
-    compute_all_rays_from();
-    compute_all_lines();
-
     board.total_occupancy();
     board.update_full_cache();
 
@@ -0,0 +1,357 @@
+#[allow(unused_imports)]
+use crate::{
+    bench::{BENCH_POSITIONS, run_bench},
+    converters::{
+        fen_converter::{STARTING_POSITION_FEN, fen_to_board},
+        move_converter::{move_to_uci, uci_to_move},
+    },
+    search::{Engine, MAX_HISTORY_SCORE, MAX_SEARCH_DEPTH, SearchLimits, score_to_uci},
+    time_management::TimeManager,
+    transposition::TranspositionTable,
+    uci::parse_position,
+};
+#[allow(unused_imports)]
+use std::sync::Arc;
+
+#[cfg(test)]
+fn search_score(fen: &str, depth: u8) -> String {
+    let (board, mut state) = fen_to_board(fen);
+    let mut engine: Engine = Engine::new(state.whose_turn, depth);
+    engine.transposition_table = Arc::new(TranspositionTable::new(16));
+    engine.own_book = false;
+    engine.find_best_move(&board, &mut state, SearchLimits::depth(depth));
+    return score_to_uci(engine.pv_lines[0].score);
+}
+
+#[test]
+fn mate_score_test1() -> () {
+    // Ra7 and Rb8#
+    assert_eq!(search_score("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1", 5), "mate 2");
+}
+
+#[test]
+fn mate_score_test2() -> () {
+    assert_eq!(
+        search_score("7k/R7/8/8/8/8/8/1R4K1 b - - 0 1", 4),
+        "mate -1"
+    );
+}
+
+#[test]
+fn check_extension_test1() -> () {
+    // the mate takes 5 plies, reachable at depth 4 only by extending the checks
+    assert_eq!(
+        search_score("r5rk/5p1p/5R2/4B3/8/8/7P/7K w - - 0 1", 4),
+        "mate 3"
+    );
+}
+
+#[test]
+fn concurrent_engines_test1() -> () {
+    // the endgame king tables of one engine must not leak into the other one's evaluation
+    let fens: [&str; 2] = [
+        "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+        "8/5k2/8/3K4/8/8/4P3/8 w - - 0 1",
+    ];
+    let alone: Vec<String> = fens.iter().map(|fen| search_score(fen, 6)).collect();
+    let together: Vec<String> = std::thread::scope(|s| {
+        let handles: Vec<_> = fens
+            .iter()
+            .map(|fen| s.spawn(move || search_score(fen, 6)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(alone, together);
+}
+
+#[test]
+fn contempt_test1() -> () {
+    let draws: [&str; 2] = [
+        "7k/8/8/8/8/8/8/K5q1 w - - 99 80", // fifty-move rule after any reply
+        "8/8/8/4k3/8/8/2N5/4K3 w - - 0 1", // insufficient material
+    ];
+    for fen in draws {
+        for contempt in [-20, 0, 35] {
+            let (board, mut state) = fen_to_board(fen);
+            let mut engine: Engine = Engine::new(state.whose_turn, 3);
+            engine.transposition_table = Arc::new(TranspositionTable::new(16));
+            engine.own_book = false;
+            engine.contempt = contempt;
+            engine.find_best_move(&board, &mut state, SearchLimits::depth(3));
+            assert_eq!(engine.pv_lines[0].score, -contempt, "{fen}");
+        }
+    }
+}
+
+#[test]
+fn bench_test1() -> () {
+    // the node count is what builds are compared by, so it must not depend on the previous run
+    let mut engine: Engine = Engine::new(8, 5);
+    engine.transposition_table = Arc::new(TranspositionTable::new(16));
+    let first: u64 = run_bench(&mut engine, 5);
+    assert!(first > 0);
+    assert_eq!(run_bench(&mut engine, 5), first);
+}
+
+#[test]
+fn node_limit_test1() -> () {
+    // the threads report their nodes every 2500, so each other thread's last batch may be missed
+    let (board, mut state) = fen_to_board(STARTING_POSITION_FEN);
+    for threads in [1, 4] {
+        let mut engine: Engine = Engine::new(8, MAX_SEARCH_DEPTH);
+        engine.transposition_table = Arc::new(TranspositionTable::new(16));
+        engine.own_book = false;
+        engine.threads = threads;
+        let limits: SearchLimits = SearchLimits {
+            depth: MAX_SEARCH_DEPTH,
+            nodes: Some(100_000),
+            mate: None,
+            time_manager: TimeManager::infinite(),
+            search_moves: Vec::new(),
+        };
+        assert!(engine.find_best_move(&board, &mut state, limits).is_some());
+        assert!(
+            engine.searched_nodes <= 100_000 + (threads as u64 - 1) * 2500,
+            "{threads} threads: {}",
+            engine.searched_nodes
+        );
+    }
+}
+
+#[test]
+fn search_moves_test1() -> () {
+    // the book has other moves for the starting position, they must not be played either
+    let (board, mut state) = fen_to_board(STARTING_POSITION_FEN);
+    for (own_book, allowed) in [(true, "d2d4"), (false, "g2g4"), (false, "b1a3")] {
+        let mut engine: Engine = Engine::new(8, 5);
+        engine.transposition_table = Arc::new(TranspositionTable::new(16));
+        engine.own_book = own_book;
+        let mut limits: SearchLimits = SearchLimits::depth(5);
+        limits.search_moves = vec![uci_to_move(&board, &state, allowed).unwrap()];
+        let best_move: u16 = engine.find_best_move(&board, &mut state, limits).unwrap();
+        assert_eq!(move_to_uci(best_move, false), allowed);
+    }
+}
+
+#[test]
+fn pv_test1() -> () {
+    // the lines cut by TT hits are continued from the table, and the whole line stays playable
+    let fen: &str = "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 8";
+    let (board, mut state) = fen_to_board(fen);
+    let mut engine: Engine = Engine::new(8, 8);
+    engine.transposition_table = Arc::new(TranspositionTable::new(16));
+    engine.own_book = false;
+    engine.find_best_move(&board, &mut state, SearchLimits::depth(8));
+    let line: Vec<String> = engine.pv_lines[0]
+        .line
+        .iter()
+        .map(|m| move_to_uci(*m, false))
+        .collect();
+    assert!(line.len() >= 4, "{line:?}");
+    assert!(parse_position(&format!("position fen {fen} moves {}", line.join(" "))).is_ok());
+}
+
+#[test]
+fn multi_pv_test1() -> () {
+    let fen: &str = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+    let (board, mut state) = fen_to_board(fen);
+    let mut engine: Engine = Engine::new(8, 6);
+    engine.transposition_table = Arc::new(TranspositionTable::new(16));
+    engine.own_book = false;
+    engine.multi_pv = 3;
+    engine.find_best_move(&board, &mut state, SearchLimits::depth(6));
+
+    let first_moves: Vec<String> = engine
+        .pv_lines
+        .iter()
+        .map(|l| move_to_uci(l.line[0], false))
+        .collect();
+    assert_eq!(first_moves.len(), 3);
+    for (i, first_move) in first_moves.iter().enumerate() {
+        assert!(!first_moves[..i].contains(first_move), "{first_moves:?}");
+        assert!(parse_position(&format!("position fen {fen} moves {first_move}")).is_ok());
+    }
+    assert!(engine.pv_lines.windows(2).all(|l| l[0].score >= l[1].score));
+
+    // "searchmoves" leaves fewer lines than asked for
+    let mut limits: SearchLimits = SearchLimits::depth(6);
+    limits.search_moves = ["d2d3", "b1c3"]
+        .iter()
+        .map(|m| uci_to_move(&board, &state, m).unwrap())
+        .collect();
+    engine.find_best_move(&board, &mut state, limits);
+    let mut first_moves: Vec<String> = engine
+        .pv_lines
+        .iter()
+        .map(|l| move_to_uci(l.line[0], false))
+        .collect();
+    first_moves.sort();
+    assert_eq!(first_moves, ["b1c3", "d2d3"]);
+
+    // the opening generator's helper keeps only the lines close to the best one
+    let all_moves: Vec<Option<u16>> =
+        engine.find_multiple_moves(&board, &mut state, 6, 3, 8, 10_000);
+    assert_eq!(all_moves.len(), 3);
+    let best_moves: Vec<Option<u16>> = engine.find_multiple_moves(&board, &mut state, 6, 3, 8, 0);
+    assert!(!best_moves.is_empty() && best_moves[0] == all_moves[0]);
+    assert_eq!(engine.multi_pv, 3);
+}
+
+#[test]
+fn aspiration_window_test1() -> () {
+    // on tactical positions the windows only save work, the result is the one of a full window
+    // search
+    let positions: [(&str, &str); 5] = [
+        (
+            "r1bq2rk/pp3pbp/2p1p1pQ/7P/3P4/2PB1N2/PP3PPR/2KR4 w - - 0 1",
+            "h6h7",
+        ),
+        ("5k2/6pp/p1qN4/1p1p4/3P4/2PKP2Q/PP3r2/3R4 b - - 0 1", "c6c4"),
+        (
+            "rnbqkb1r/pppp1ppp/8/4P3/6n1/7P/PPPNPPP1/R1BQKBNR b KQkq - 0 1",
+            "g4e3",
+        ),
+        (
+            "r3q1kr/ppp5/3p2pQ/8/3PP1b1/5R2/PPP3P1/5RK1 w - - 0 1",
+            "f3f8",
+        ),
+        (
+            "4k1r1/2p3r1/1pR1p3/3pP2p/3P2qP/P4N2/1PQ4P/5R1K b - - 0 1",
+            "g4f3",
+        ),
+    ];
+    for (fen, solution) in positions {
+        let results: Vec<(u16, i32)> = [true, false]
+            .iter()
+            .map(|&aspiration_windows| {
+                let (board, mut state) = fen_to_board(fen);
+                let mut engine: Engine = Engine::new(state.whose_turn, 7);
+                engine.transposition_table = Arc::new(TranspositionTable::new(16));
+                engine.own_book = false;
+                engine.aspiration_windows = aspiration_windows;
+                let best_move: u16 = engine
+                    .find_best_move(&board, &mut state, SearchLimits::depth(7))
+                    .unwrap();
+                (best_move, engine.pv_lines[0].score)
+            })
+            .collect();
+        assert_eq!(results[0], results[1], "{fen}");
+        assert_eq!(move_to_uci(results[0].0, false), solution, "{fen}");
+    }
+}
+
+#[test]
+fn aspiration_window_test2() -> () {
+    // the mate is only seen at depth 4, where the window around the score of depth 3 fails high
+    // and has to open up to the mate score
+    let fen: &str = "r5rk/5p1p/5R2/4B3/8/8/7P/7K w - - 0 1";
+    assert!(!search_score(fen, 3).starts_with("mate"));
+    let (board, mut state) = fen_to_board(fen);
+    let mut engine: Engine = Engine::new(8, 6);
+    engine.transposition_table = Arc::new(TranspositionTable::new(16));
+    engine.own_book = false;
+    engine.find_best_move(&board, &mut state, SearchLimits::depth(6));
+    assert_eq!(score_to_uci(engine.pv_lines[0].score), "mate 3");
+    assert_eq!(engine.pv_lines[0].flag, 0);
+}
+
+#[test]
+fn continuation_history_test1() -> () {
+    // the gravity bonus keeps every quiet history inside its bounds, however many cutoffs it gets
+    let mut engine: Engine = Engine::new(8, 9);
+    engine.transposition_table = Arc::new(TranspositionTable::new(16));
+    engine.own_book = false;
+    for fen in &BENCH_POSITIONS[..4] {
+        let (board, mut state) = fen_to_board(fen);
+        engine.side = state.whose_turn;
+        engine.find_best_move(&board, &mut state, SearchLimits::depth(9));
+
+        let in_bounds = |h: &i16| (-MAX_HISTORY_SCORE..=MAX_HISTORY_SCORE).contains(h);
+        assert!(engine.history_heuristics.iter().all(in_bounds), "{fen}");
+        for table in &engine.continuation_history {
+            assert!(table.iter().flatten().all(in_bounds), "{fen}");
+        }
+    }
+    assert!(engine.counter_moves.iter().any(|m| *m != 0));
+    assert!(
+        engine.continuation_history[1]
+            .iter()
+            .flatten()
+            .any(|h| *h != 0)
+    );
+}
+
+#[test]
+fn capture_history_test1() -> () {
+    // refuting captures gain, the ones tried before them lose, and both stay inside the bounds
+    let mut engine: Engine = Engine::new(8, 9);
+    engine.transposition_table = Arc::new(TranspositionTable::new(16));
+    engine.own_book = false;
+    for fen in &BENCH_POSITIONS[7..10] {
+        let (board, mut state) = fen_to_board(fen);
+        engine.side = state.whose_turn;
+        engine.find_best_move(&board, &mut state, SearchLimits::depth(9));
+        assert!(
+            engine
+                .capture_history
+                .iter()
+                .all(|h| (-MAX_HISTORY_SCORE..=MAX_HISTORY_SCORE).contains(h)),
+            "{fen}"
+        );
+    }
+    assert!(engine.capture_history.iter().any(|h| *h > 0));
+    assert!(engine.capture_history.iter().any(|h| *h < 0));
+}
+
+#[test]
+fn late_move_pruning_test1() -> () {
+    // quiet moves are pruned only at shallow depth, the quiet solutions are still found
+    let positions: [(&str, u8, &str); 3] = [
+        ("7k/p7/1R5K/6r1/6p1/6P1/8/8 w - - 0 1", 8, "b6b7"),
+        (
+            "r4q1k/p2bR1rp/2p2Q1N/5p2/5p2/2P5/PP3PPP/R5K1 w - - 0 1",
+            8,
+            "e7f7",
+        ),
+        (
+            "1R6/1brk2p1/4p2p/p1P1Pp2/P7/6P1/1P4P1/2R3K1 w - - 0 1",
+            6,
+            "b8b7",
+        ),
+    ];
+    for (fen, depth, solution) in positions {
+        let (board, mut state) = fen_to_board(fen);
+        let mut engine: Engine = Engine::new(state.whose_turn, depth);
+        engine.transposition_table = Arc::new(TranspositionTable::new(16));
+        engine.own_book = false;
+        let best_move: u16 = engine
+            .find_best_move(&board, &mut state, SearchLimits::depth(depth))
+            .unwrap();
+        assert_eq!(move_to_uci(best_move, false), solution, "{fen}");
+    }
+    // mate and stalemate are still told apart at the depths where quiet moves are pruned: Qf8 mates,
+    // Qf7 stalemates
+    assert_eq!(search_score("7k/8/6K1/8/8/8/8/5Q2 w - - 0 1", 3), "mate 1");
+}
+
+#[test]
+fn iir_test1() -> () {
+    // one ply less only for nodes deep enough, without a TT move and outside singular searches
+    let (board, state) = fen_to_board(STARTING_POSITION_FEN);
+    let e2e4: u16 = uci_to_move(&board, &state, "e2e4").unwrap();
+    assert_eq!(Engine::iir_reduction(8, 0, 0), 1);
+    assert_eq!(Engine::iir_reduction(3, 0, 0), 0);
+    assert_eq!(Engine::iir_reduction(8, e2e4, 0), 0);
+    assert_eq!(Engine::iir_reduction(8, 0, e2e4), 0);
+
+    // the reduced nodes still resolve a tactic at a fixed depth
+    let (board, mut state) =
+        fen_to_board("2br2k1/2q3rn/p2NppQ1/2p1P3/Pp5R/4P3/1P3PPP/3R2K1 w - - 0 1");
+    let mut engine: Engine = Engine::new(8, 8);
+    engine.transposition_table = Arc::new(TranspositionTable::new(16));
+    engine.own_book = false;
+    let best_move: u16 = engine
+        .find_best_move(&board, &mut state, SearchLimits::depth(8))
+        .unwrap();
+    assert_eq!(move_to_uci(best_move, false), "h4h7");
+}
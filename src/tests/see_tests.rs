@@ -0,0 +1,38 @@
+#[allow(unused_imports)]
+use crate::converters::{fen_converter::fen_to_board, move_converter::uci_to_move};
+
+#[cfg(test)]
+fn see_of(fen: &str, uci: &str) -> i32 {
+    let (board, state) = fen_to_board(fen);
+    return board.see(uci_to_move(&board, &state, uci).unwrap());
+}
+
+#[test]
+fn see_test1() -> () {
+    // undefended pawn
+    assert_eq!(
+        see_of("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+        100
+    );
+    // the queen takes a pawn defended by a pawn
+    assert_eq!(see_of("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1", "d2d5"), -900);
+    // the knight is lost for a pawn, the rest of the exchange does not pay off for white
+    assert_eq!(
+        see_of(
+            "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+            "d3e5"
+        ),
+        -230
+    );
+}
+
+#[test]
+fn see_test2() -> () {
+    // the queen behind the rook recaptures, so black does not take back
+    assert_eq!(see_of("3r2k1/8/8/3p4/8/8/3R4/3Q2K1 w - - 0 1", "d2d5"), 100);
+    assert_eq!(see_of("3r2k1/8/8/3p4/8/8/3R4/6K1 w - - 0 1", "d2d5"), -400);
+    // the king cannot recapture a defended piece
+    assert_eq!(see_of("8/8/4k3/3p4/8/8/3R4/3R2K1 w - - 0 1", "d2d5"), 100);
+    // en passant
+    assert_eq!(see_of("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+}
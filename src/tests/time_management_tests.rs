@@ -0,0 +1,76 @@
+#[allow(unused_imports)]
+use crate::{
+    search::Engine,
+    time_management::{MOVE_OVERHEAD_MS, TimeManager},
+};
+#[allow(unused_imports)]
+use std::{
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn move_time_test1() -> () {
+    // "go movetime": both limits are the given time minus the overhead, whatever the search finds
+    let mut time_manager: TimeManager = TimeManager::from_move_time(1000, MOVE_OVERHEAD_MS);
+    assert_eq!(time_manager.hard_limit_ms, 1000 - MOVE_OVERHEAD_MS as u128);
+    assert_eq!(time_manager.soft_limit_ms, time_manager.hard_limit_ms);
+    time_manager.update(1, 0);
+    time_manager.update(2, -300);
+    assert_eq!(time_manager.soft_limit_ms, time_manager.hard_limit_ms);
+
+    // less than the overhead still leaves some time
+    assert_eq!(
+        TimeManager::from_move_time(10, MOVE_OVERHEAD_MS).hard_limit_ms,
+        1
+    );
+}
+
+#[test]
+fn clock_limits_test1() -> () {
+    // sudden death: a new iteration stops long before a single one is aborted
+    let time_manager: TimeManager = TimeManager::from_clock(60_000, 0, None, MOVE_OVERHEAD_MS);
+    assert!(time_manager.soft_limit_ms < time_manager.hard_limit_ms);
+
+    // increments and fewer moves to the time control both give more time
+    let with_increment: TimeManager = TimeManager::from_clock(60_000, 1000, None, MOVE_OVERHEAD_MS);
+    assert!(with_increment.optimum_ms > time_manager.optimum_ms);
+    let few_moves: TimeManager = TimeManager::from_clock(60_000, 0, Some(5), MOVE_OVERHEAD_MS);
+    assert!(few_moves.optimum_ms > time_manager.optimum_ms);
+}
+
+#[test]
+fn clock_limits_test2() -> () {
+    // however the search goes, no move takes more than the clock minus the overhead
+    for time_left in [10, 100, 1000, 10_000, 300_000] {
+        for increment in [0, 100, 2000, 20_000] {
+            for moves_to_go in [None, Some(1), Some(2), Some(40)] {
+                let mut time_manager: TimeManager =
+                    TimeManager::from_clock(time_left, increment, moves_to_go, MOVE_OVERHEAD_MS);
+                let usable_time: u128 = (time_left.saturating_sub(MOVE_OVERHEAD_MS) as u128).max(1);
+                for (best_move, score) in [(1, 0), (2, -500), (2, -500), (3, 200)] {
+                    time_manager.update(best_move, score);
+                    assert!(time_manager.soft_limit_ms <= time_manager.hard_limit_ms);
+                    assert!(
+                        time_manager.hard_limit_ms <= usable_time,
+                        "{time_left} {increment} {moves_to_go:?}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn ponderhit_test1() -> () {
+    // the time spent pondering before "ponderhit" is not taken from our clock
+    let mut engine: Engine = Engine::new(8, 5);
+    engine.time_manager = TimeManager::from_move_time(1000, MOVE_OVERHEAD_MS);
+    engine.time_manager.start_time = Instant::now() - Duration::from_millis(500);
+    engine.signals.ponderhit_ms.store(400, Ordering::Relaxed);
+    let clock_time: u128 = engine.clock_time_ms();
+    assert!((100..400).contains(&clock_time), "{clock_time}");
+
+    engine.signals.ponderhit_ms.store(0, Ordering::Relaxed);
+    assert!(engine.clock_time_ms() >= 500);
+}
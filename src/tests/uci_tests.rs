@@ -0,0 +1,124 @@
+#[allow(unused_imports)]
+use crate::{
+    converters::fen_converter::fen_to_board,
+    search::Engine,
+    transposition::TranspositionTable,
+    uci::{options::set_option, parse_position},
+};
+#[allow(unused_imports)]
+use std::sync::Arc;
+
+#[test]
+fn position_moves_test1() -> () {
+    let (board, state) = parse_position(
+        "position startpos moves e2e4 g8f6 e4e5 d7d5 e5d6 e7d6 g1f3 f8e7 f1c4 e8g8 e1g1",
+    )
+    .unwrap();
+    let (expected_board, expected_state) =
+        fen_to_board("rnbq1rk1/ppp1bppp/3p1n2/8/2B5/5N2/PPPP1PPP/RNBQ1RK1 b - - 5 6");
+
+    assert_eq!(board.bitboards, expected_board.bitboards);
+    assert_eq!(board.cached_pieces, expected_board.cached_pieces);
+    assert_eq!(state.castling_rights, expected_state.castling_rights);
+    assert_eq!(state.whose_turn, expected_state.whose_turn);
+    assert_eq!(state.fifty_moves_rule_counter, 5);
+    assert_eq!(state.irreversible_moves.len(), 5);
+}
+
+#[test]
+fn position_moves_test2() -> () {
+    let (board, state) =
+        parse_position("position fen 8/P6k/8/8/8/8/6K1/8 w - - 0 60 moves a7a8n h7g6").unwrap();
+    let (expected_board, _) = fen_to_board("N7/8/6k1/8/8/8/6K1/8 w - - 1 61");
+
+    assert_eq!(board.bitboards, expected_board.bitboards);
+    assert_eq!(state.whose_turn, 8);
+    assert_eq!(state.total_moves_amount, 61);
+}
+
+#[test]
+fn invalid_position_test1() -> () {
+    // bad piece, missing king, wrong rank length, side not to move in check
+    assert!(
+        parse_position("position fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w - - 0 1")
+            .is_err()
+    );
+    assert!(parse_position("position fen 8/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+    assert!(parse_position("position fen 4k3/8/8/8/8/8/8/4K4 w - - 0 1").is_err());
+    assert!(parse_position("position fen 4k3/8/8/8/8/8/8/4R1K1 w - - 0 1").is_err());
+    assert!(
+        parse_position("position fen rnbqkbnrR/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w - - 0 1")
+            .is_err()
+    );
+    // en passant squares on the mover's side or without the pawn that moved two squares
+    assert!(parse_position("position fen 4k3/8/8/8/8/8/3PP3/4K3 w - e3 0 1").is_err());
+    assert!(parse_position("position fen 4k3/8/8/8/8/8/3PP3/4K3 b - e3 0 1").is_err());
+    assert!(parse_position("position fen 4k3/8/8/3pP3/8/8/8/4K3 w - e6 0 1").is_err());
+    // the target or the square the pawn came from is occupied
+    assert!(parse_position("position fen 4k3/8/3n4/3pP3/8/8/8/4K3 w - d6 0 1").is_err());
+    assert!(parse_position("position fen 4k3/3b4/8/3pP3/8/8/8/4K3 w - d6 0 1").is_err());
+    assert!(parse_position("position fen 4k3/8/8/8/3Pp3/3N4/8/4K3 b - d3 0 1").is_err());
+    assert!(parse_position("position fen 4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").is_ok());
+    // illegal moves: a pinned piece and a king walking into check
+    assert!(parse_position("position fen 4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1 moves e2d3").is_err());
+    assert!(parse_position("position startpos moves e2e4 d7d5 e1e2 c8g4 e2f3").is_err());
+    assert!(parse_position("position startpos moves e2e5").is_err());
+}
+
+#[test]
+fn repetition_test1() -> () {
+    let (board, state) = parse_position("position startpos moves g1f3 g8f6 f3g1 f6g8").unwrap();
+    assert!(state.is_repetition(Engine::rebuild_hash(&board, &state, 8)));
+
+    let (board, state) = parse_position("position startpos moves g1f3 g8f6 f3g1").unwrap();
+    assert!(!state.is_repetition(Engine::rebuild_hash(&board, &state, 16)));
+
+    // the positions before the pawn move are dropped
+    let (board, state) =
+        parse_position("position startpos moves g1f3 e7e6 f3g1 g8f6 g1f3 f6g8").unwrap();
+    assert_eq!(state.irreversible_moves.len(), 4);
+    assert!(state.is_repetition(Engine::rebuild_hash(&board, &state, 8)));
+}
+
+#[test]
+fn set_option_test1() -> () {
+    let mut engine: Engine = Engine::new(8, 5);
+    set_option(&mut engine, "setoption name Threads value 4").unwrap();
+    set_option(&mut engine, "setoption name MultiPV value 3").unwrap();
+    set_option(&mut engine, "setoption name Contempt value -20").unwrap();
+    // names of several words, in any case
+    set_option(&mut engine, "setoption name Move Overhead value 100").unwrap();
+    set_option(&mut engine, "setoption name uci_chess960 value true").unwrap();
+    set_option(&mut engine, "setoption name OwnBook value FALSE").unwrap();
+    assert_eq!(engine.threads, 4);
+    assert_eq!(engine.multi_pv, 3);
+    assert_eq!(engine.contempt, -20);
+    assert_eq!(engine.move_overhead_ms, 100);
+    assert!(engine.chess960);
+    assert!(!engine.own_book);
+
+    let old_table: Arc<TranspositionTable> = engine.transposition_table.clone();
+    set_option(&mut engine, "setoption name Hash value 2").unwrap();
+    assert!(!Arc::ptr_eq(&old_table, &engine.transposition_table));
+    set_option(&mut engine, "setoption name Clear Hash").unwrap();
+}
+
+#[test]
+fn set_option_test2() -> () {
+    // out of range, not a number, not a bool, no value, unknown name, no "name"
+    let mut engine: Engine = Engine::new(8, 5);
+    for command in [
+        "setoption name Threads value 0",
+        "setoption name Threads value 257",
+        "setoption name Contempt value 101",
+        "setoption name Hash value big",
+        "setoption name OwnBook value yes",
+        "setoption name MultiPV",
+        "setoption name Ponder value true",
+        "setoption Threads value 2",
+    ] {
+        assert!(set_option(&mut engine, command).is_err(), "{command}");
+    }
+    assert_eq!(engine.threads, 1);
+    assert!(engine.own_book);
+}
@@ -0,0 +1,99 @@
+use std::time::Instant;
+
+const DEFAULT_MOVES_TO_GO: u64 = 30; // when the GUI does not send "movestogo"
+const HARD_LIMIT_MULTIPLIER: u128 = 4; // how much longer than optimal a single move may take
+const MAX_TIME_USAGE_PERCENT: u128 = 75; // never use more than this part of the clock on one move
+pub const MOVE_OVERHEAD_MS: u64 = 30; // lag between the engine and the GUI clock
+
+// percent of the optimal time depending on how many iterations in a row kept the best move
+const BEST_MOVE_STABILITY_SCALE: [u128; 5] = [150, 120, 100, 85, 70];
+const MAX_SCORE_DROP_BONUS: i32 = 50; // extra percent of time when the score falls
+const SCORE_DROP_DIVISOR: i32 = 2; // centipawns lost per extra percent
+
+pub struct TimeManager {
+    pub start_time: Instant,
+    pub optimum_ms: u128,    // time for a move where nothing special happens
+    pub soft_limit_ms: u128, // no new iteration is started after it
+    pub hard_limit_ms: u128, // the search is aborted after it
+    pub fixed_time: bool,    // "go movetime", the limits are not adjusted
+    pub best_move_stability: usize,
+    previous_best_move: u16,
+    previous_score: Option<i32>,
+}
+
+impl TimeManager {
+    pub fn infinite() -> Self {
+        return Self::with_limits(u128::MAX, u128::MAX, true);
+    }
+
+    pub fn from_move_time(move_time_ms: u64, move_overhead_ms: u64) -> Self {
+        let limit: u128 = move_time_ms.saturating_sub(move_overhead_ms).max(1) as u128;
+        return Self::with_limits(limit, limit, true);
+    }
+
+    pub fn from_clock(
+        time_left_ms: u64,
+        increment_ms: u64,
+        moves_to_go: Option<u64>,
+        move_overhead_ms: u64,
+    ) -> Self {
+        let usable_time: u128 = time_left_ms.saturating_sub(move_overhead_ms).max(1) as u128;
+        let moves_to_go: u128 = moves_to_go
+            .unwrap_or(DEFAULT_MOVES_TO_GO)
+            .clamp(1, DEFAULT_MOVES_TO_GO) as u128;
+
+        let hard_limit: u128 = ((usable_time / moves_to_go + increment_ms as u128)
+            * HARD_LIMIT_MULTIPLIER)
+            .min(usable_time * MAX_TIME_USAGE_PERCENT / 100)
+            .max(1);
+        let optimum: u128 =
+            (usable_time / moves_to_go + (increment_ms as u128) * 3 / 4).min(hard_limit);
+
+        return Self::with_limits(optimum, hard_limit, false);
+    }
+
+    fn with_limits(optimum_ms: u128, hard_limit_ms: u128, fixed_time: bool) -> Self {
+        return Self {
+            start_time: Instant::now(),
+            optimum_ms,
+            soft_limit_ms: optimum_ms,
+            hard_limit_ms,
+            fixed_time,
+            best_move_stability: 0,
+            previous_best_move: 0,
+            previous_score: None,
+        };
+    }
+
+    #[inline(always)]
+    pub fn elapsed_ms(&self) -> u128 {
+        return self.start_time.elapsed().as_millis();
+    }
+
+    // called after every finished iteration: a stable best move saves time,
+    // a changing best move or a falling score asks for more
+    pub fn update(&mut self, best_move: u16, score: i32) -> () {
+        if best_move == self.previous_best_move {
+            self.best_move_stability =
+                (self.best_move_stability + 1).min(BEST_MOVE_STABILITY_SCALE.len() - 1);
+        } else {
+            self.best_move_stability = 0;
+        }
+        let score_drop_bonus: i32 = if let Some(previous_score) = self.previous_score {
+            ((previous_score - score) / SCORE_DROP_DIVISOR).clamp(0, MAX_SCORE_DROP_BONUS)
+        } else {
+            0
+        };
+        self.previous_best_move = best_move;
+        self.previous_score = Some(score);
+
+        if self.fixed_time {
+            return;
+        }
+        self.soft_limit_ms = (self.optimum_ms
+            * BEST_MOVE_STABILITY_SCALE[self.best_move_stability]
+            * (100 + score_drop_bonus as u128)
+            / 10_000)
+            .min(self.hard_limit_ms);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::search::MATE_SCORE_THRESHOLD;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 #[derive(Clone, Copy)]
 pub struct TTEntry {
     pub hash: u64,
@@ -7,77 +10,153 @@ pub struct TTEntry {
     pub best_move: u16,
 }
 
-const TT_LEN: usize = 8 * 1024 * 1024; // 32 MB
+pub const DEFAULT_HASH_MB: usize = 256;
 const BUCKET_LEN: usize = 2;
+const HASHFULL_SAMPLE: usize = 1000; // entries looked at to estimate "hashfull"
+
+// the table is shared by all the search threads without locks. an entry is stored as
+// its packed data and the hash xored with that data, so an entry torn by two threads
+// writing at once no longer matches any hash and reads as a miss
+#[derive(Default)]
+struct TTSlot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl TTSlot {
+    #[inline(always)]
+    fn load(&self) -> TTEntry {
+        let data: u64 = self.data.load(Ordering::Relaxed);
+        let key: u64 = self.key.load(Ordering::Relaxed);
+        return TTEntry {
+            hash: key ^ data,
+            score: data as u32 as i32,
+            depth: ((data >> 32) & 0xFF) as usize,
+            flag: ((data >> 40) & 0xFF) as u8,
+            best_move: (data >> 48) as u16,
+        };
+    }
+
+    #[inline(always)]
+    fn store(&self, entry: &TTEntry) -> () {
+        let data: u64 = entry.score as u32 as u64
+            | (entry.depth.min(u8::MAX as usize) as u64) << 32
+            | (entry.flag as u64) << 40
+            | (entry.best_move as u64) << 48;
+        self.data.store(data, Ordering::Relaxed);
+        self.key.store(entry.hash ^ data, Ordering::Relaxed);
+    }
+}
 
 pub struct TranspositionTable {
-    pub entries: Box<[[TTEntry; BUCKET_LEN]]>,
-    pub collisions: u64,
-    pub replacements: u64,
-    pub occupied: u64,
-    pub hits: u64,
+    entries: Box<[[TTSlot; BUCKET_LEN]]>,
 }
 
 impl TranspositionTable {
-    pub fn new() -> Self {
-        return Self {
-            entries: vec![
-                [TTEntry {
-                    hash: 0,
-                    score: 0,
-                    depth: 0,
-                    flag: 3, // no flag
-                    best_move: 0,
-                }; BUCKET_LEN];
-                TT_LEN
-            ]
-            .into_boxed_slice(),
-            collisions: 0,
-            replacements: 0,
-            occupied: 0,
-            hits: 0,
-        };
+    pub fn new(size_mb: usize) -> Self {
+        return Self::try_new(size_mb).unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    // the size asked by the GUI may be more than the machine can give
+    pub fn try_new(size_mb: usize) -> Result<Self, String> {
+        let buckets_amount: usize =
+            (size_mb * 1024 * 1024 / size_of::<[TTSlot; BUCKET_LEN]>()).max(1);
+        // only checks that the memory can be had: filling this vector would write every
+        // entry, while the collected table below is allocated zeroed and touched lazily
+        Vec::<[TTSlot; BUCKET_LEN]>::new()
+            .try_reserve_exact(buckets_amount)
+            .map_err(|_| format!("cannot allocate {size_mb} MB for the hash table"))?;
+        return Ok(Self {
+            entries: (0..buckets_amount).map(|_| Default::default()).collect(),
+        });
     }
 
-    pub fn get_entry(&mut self, hash_num: &u64) -> Option<TTEntry> {
-        let bucket: &[TTEntry; BUCKET_LEN] = &self.entries[(*hash_num as usize) & (TT_LEN - 1)];
+    pub fn clear(&self) -> () {
+        for bucket in self.entries.iter() {
+            for slot in bucket {
+                slot.key.store(0, Ordering::Relaxed);
+                slot.data.store(0, Ordering::Relaxed);
+            }
+        }
+    }
 
-        for entry in bucket {
+    // permille of the occupied entries, as UCI expects it, estimated from the first ones
+    pub fn hashfull(&self) -> u64 {
+        let sampled: usize = HASHFULL_SAMPLE.min(self.entries.len() * BUCKET_LEN);
+        let occupied: usize = self
+            .entries
+            .iter()
+            .flatten()
+            .take(sampled)
+            .filter(|slot| slot.load().hash != 0)
+            .count();
+        return (occupied * 1000 / sampled) as u64;
+    }
+
+    // maps the hash onto the table without requiring a power of two length
+    #[inline(always)]
+    fn bucket_index(&self, hash_num: &u64) -> usize {
+        return ((*hash_num as u128 * self.entries.len() as u128) >> 64) as usize;
+    }
+
+    // mate scores are stored as the distance from the entry's own node, not from the root,
+    // so that the same position reached at another ply reads the right distance
+    #[inline(always)]
+    fn score_to_tt(score: i32, ply: usize) -> i32 {
+        if score >= MATE_SCORE_THRESHOLD {
+            return score + ply as i32;
+        }
+        if score <= -MATE_SCORE_THRESHOLD {
+            return score - ply as i32;
+        }
+        return score;
+    }
+
+    #[inline(always)]
+    fn score_from_tt(score: i32, ply: usize) -> i32 {
+        if score >= MATE_SCORE_THRESHOLD {
+            return score - ply as i32;
+        }
+        if score <= -MATE_SCORE_THRESHOLD {
+            return score + ply as i32;
+        }
+        return score;
+    }
+
+    pub fn get_entry(&self, hash_num: &u64, ply: usize) -> Option<TTEntry> {
+        let bucket: &[TTSlot; BUCKET_LEN] = &self.entries[self.bucket_index(hash_num)];
+
+        for slot in bucket {
+            let entry: TTEntry = slot.load();
             if entry.hash == *hash_num {
-                return Some(*entry);
+                return Some(TTEntry {
+                    score: Self::score_from_tt(entry.score, ply),
+                    ..entry
+                });
             }
         }
         return None;
     }
 
-    pub fn record_entry(&mut self, hash_num: &u64, entry: TTEntry) -> () {
-        let entry_index: usize = (*hash_num as usize) & (TT_LEN - 1);
-        let old_entries_bucket: &mut [TTEntry; BUCKET_LEN] = &mut self.entries[entry_index];
+    pub fn record_entry(&self, hash_num: &u64, mut entry: TTEntry, ply: usize) -> () {
+        entry.score = Self::score_to_tt(entry.score, ply);
+        let bucket: &[TTSlot; BUCKET_LEN] = &self.entries[self.bucket_index(hash_num)];
         let mut worst_entry_idx: usize = 0;
+        let mut worst_entry: TTEntry = bucket[0].load();
 
         for idx in 0..BUCKET_LEN {
-            {
-                let old_entry: &mut TTEntry = &mut old_entries_bucket[idx];
-                if *hash_num == old_entry.hash {
-                    old_entries_bucket[idx] = entry;
-                    return ();
-                }
+            let old_entry: TTEntry = bucket[idx].load();
+            if *hash_num == old_entry.hash {
+                bucket[idx].store(&entry);
+                return ();
             }
-            let old_entry: &TTEntry = &old_entries_bucket[idx];
-            let old_worst_entry: &TTEntry = &old_entries_bucket[worst_entry_idx];
-            if old_entry.depth < old_worst_entry.depth {
-                worst_entry_idx = idx;
+            if old_entry.depth < worst_entry.depth {
+                (worst_entry_idx, worst_entry) = (idx, old_entry);
             }
         }
-        let old_entry: &mut TTEntry = &mut old_entries_bucket[worst_entry_idx];
-        if old_entry.hash == 0 {
-            self.occupied += 1
-        } else {
-            if entry.depth < old_entry.depth {
-                return ();
-            }
-            self.replacements += 1;
+        if worst_entry.hash != 0 && entry.depth < worst_entry.depth {
+            return ();
         }
-        *old_entry = entry;
+        bucket[worst_entry_idx].store(&entry);
     }
 }
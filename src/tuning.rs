@@ -1,32 +1,30 @@
 use crate::{
     board::Board,
     board_geometry_templates::*,
-    constants::{
-        attacks::{BLACK_PAWN_ATTACKS, MVV_LVA, WHITE_PAWN_ATTACKS},
-        heuristics::*,
-    },
-    search::Engine,
+    constants::attacks::{BLACK_PAWN_ATTACKS, MVV_LVA, WHITE_PAWN_ATTACKS},
+    search::{CAPTURE_HISTORY_DIVISOR, COUNTERMOVE_BONUS, Engine},
 };
 const LAZY_SORT_LEN: usize = 8;
+const LOSING_CAPTURE_BASE: i16 = -1000; // below every quiet move
 
 impl Engine {
     #[inline(always)]
     pub fn score_all_moves(
         &mut self,
-        depth: usize,
+        ply: usize,
         last_occupied: usize,
         previous_best_move: &u16,
         current_board: &Board,
         show_scores: bool,
     ) -> () {
-        let pseudo_moves: &[u16; 192] = &self.move_lists[depth].pseudo_moves;
+        let pseudo_moves: &[u16; 192] = &self.move_lists[ply].pseudo_moves;
         for i in 0..last_occupied {
             let mv: u16 = pseudo_moves[0..last_occupied][i];
 
             if pseudo_moves[i] == *previous_best_move {
-                self.move_scores[depth][i] = i16::MAX;
+                self.move_scores[ply][i] = i16::MAX;
             } else {
-                self.move_scores[depth][i] = self.move_priority(&mv, depth, current_board);
+                self.move_scores[ply][i] = self.move_priority(&mv, ply, current_board);
             }
             if show_scores {
                 println!(
@@ -40,7 +38,7 @@ impl Engine {
     }
 
     #[inline(always)]
-    pub fn move_priority(&self, m: &u16, depth: usize, current_board: &Board) -> i16 {
+    pub fn move_priority(&self, m: &u16, ply: usize, current_board: &Board) -> i16 {
         let mut score: i16 = 0;
         let to_square: usize = to_square(*m) as usize;
         let flag: u16 = (m & MARK_MASK) >> MARK_SHIFT;
@@ -56,7 +54,7 @@ impl Engine {
             other => other * 20,
         };
 
-        if taken_piece_type != 0 {
+        if taken_piece_type != 0 && !is_castling(*m) {
             let mut victim_value: usize = Self::get_piece_value(taken_piece_type);
             let mut attacker_value: usize = Self::get_piece_value(moving_piece_type);
             if victim_value > 5 {
@@ -65,19 +63,48 @@ impl Engine {
             if attacker_value > 5 {
                 attacker_value -= 6;
             }
+            // a cheaper piece may be defended, so those captures go through the exchange first
+            if victim_value < attacker_value && flag < 7 {
+                let see_score: i32 = current_board.see(*m);
+                if see_score < 0 {
+                    return score + LOSING_CAPTURE_BASE + see_score as i16;
+                }
+            }
 
-            return score + MVV_LVA[victim_value][attacker_value]; // get captures ahead of quiets
+            let capture_key: usize =
+                ((moving_piece_type as usize - 1) * 64 + to_square) * 6 + victim_value;
+            return score
+                + MVV_LVA[victim_value][attacker_value] // get captures ahead of quiets
+                + self.capture_history[capture_key] / CAPTURE_HISTORY_DIVISOR;
         }
         let history_idx: usize =
             (((m & FROM_MASK) as usize) << 6) | ((m & TO_MASK) >> TO_SHIFT) as usize;
         score += self.history_heuristics[history_idx] / 30;
 
-        score += Self::does_improve_piece(*m, moving_piece_type) as i16;
+        score += Self::does_improve_piece(current_board, *m, moving_piece_type) as i16;
+
+        // how the move fared after the previous two moves
+        let piece_square: usize = (moving_piece_type as usize - 1) * 64 + to_square;
+        if let Some(previous) = ply
+            .checked_sub(1)
+            .and_then(|p| self.played_piece_squares[p])
+        {
+            if self.counter_moves[previous] == *m {
+                score += COUNTERMOVE_BONUS;
+            }
+            score += self.continuation_history[0][previous][piece_square] / 30;
+        }
+        if let Some(previous) = ply
+            .checked_sub(2)
+            .and_then(|p| self.played_piece_squares[p])
+        {
+            score += self.continuation_history[1][previous][piece_square] / 30;
+        }
 
-        if self.killer_moves[depth][0] == Some(*m) {
+        if self.killer_moves[ply][0] == Some(*m) {
             // killer moves cannot be captures or checks
             score += 100;
-        } else if self.killer_moves[depth][1] == Some(*m) {
+        } else if self.killer_moves[ply][1] == Some(*m) {
             score += 80;
         }
 
@@ -156,8 +183,8 @@ impl Engine {
     }
 
     #[inline(always)]
-    pub fn does_improve_piece(m: u16, t: u16) -> i32 {
-        let heuristics_table: &[i32; 64] = unsafe { &HEURISTICS_TABLE[t as usize - 1] };
+    pub fn does_improve_piece(board: &Board, m: u16, t: u16) -> i32 {
+        let heuristics_table: &[i32; 64] = &board.heuristics()[t as usize - 1];
         return heuristics_table[to_square(m) as usize] - heuristics_table[from_square(m) as usize];
     }
 }
@@ -0,0 +1,41 @@
+use std::fmt;
+
+// everything that can be wrong with a line sent by the GUI. the engine answers
+// with "info string error: ..." and keeps its previous state
+#[derive(Debug)]
+pub enum UciError {
+    UnknownCommand(String),
+    InvalidFen(String),
+    IllegalMove(String),
+    MissingValue(String),
+    InvalidValue { parameter: String, value: String },
+    InvalidOption(String),
+    SearchRunning(String),
+    SearchFailed(String),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            UciError::UnknownCommand(command) => write!(f, "unknown command {command}"),
+            UciError::InvalidFen(reason) => write!(f, "invalid fen: {reason}"),
+            UciError::IllegalMove(m) => write!(f, "illegal move {m}"),
+            UciError::MissingValue(parameter) => write!(f, "{parameter} needs a value"),
+            UciError::InvalidValue { parameter, value } => {
+                write!(f, "invalid value {value} for {parameter}")
+            }
+            UciError::InvalidOption(reason) => write!(f, "{reason}"),
+            UciError::SearchFailed(reason) => {
+                write!(f, "the search failed ({reason}), the engine was reset")
+            }
+            UciError::SearchRunning(command) => {
+                write!(
+                    f,
+                    "{command} is not allowed while searching, send \"stop\" first"
+                )
+            }
+        };
+    }
+}
+
+impl std::error::Error for UciError {}
@@ -0,0 +1,475 @@
+use std::{
+    io::BufRead,
+    iter::Peekable,
+    panic::AssertUnwindSafe,
+    str::{FromStr, SplitWhitespace},
+    sync::{Arc, atomic::Ordering},
+    thread::ScopedJoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    bench::{BENCH_DEPTH, run_bench},
+    board::Board,
+    converters::{
+        fen_converter::{STARTING_POSITION_FEN, fen_to_board, parse_fen},
+        move_converter::{move_to_uci, uci_to_move},
+    },
+    gamestate::GameState,
+    search::{Engine, MAX_SEARCH_DEPTH, SEARCH_THREAD_STACK_SIZE, SearchLimits, SearchSignals},
+    tests,
+    time_management::TimeManager,
+    uci::{
+        error::UciError,
+        options::{print_options, set_option},
+    },
+};
+
+pub mod error;
+pub mod options;
+
+const UCI_COMMANDS: [&str; 12] = [
+    "uci",
+    "debug",
+    "isready",
+    "setoption",
+    "register",
+    "ucinewgame",
+    "position",
+    "go",
+    "stop",
+    "ponderhit",
+    "quit",
+    "bench",
+];
+
+pub fn uci_output(engine: &mut Engine) -> () {
+    let input: std::io::Lines<std::io::StdinLock<'_>> = std::io::stdin().lock().lines();
+    let (mut board, mut state): (Option<Board>, Option<GameState>) = (None, None);
+    let signals: Arc<SearchSignals> = engine.signals.clone();
+
+    std::thread::scope(|scope| {
+        // the engine is lent to the search thread and given back when the search is over
+        let mut idle_engine: Option<&mut Engine> = Some(engine);
+        let mut search_thread: Option<ScopedJoinHandle<'_, &mut Engine>> = None;
+        let mut search_start: Instant = Instant::now();
+
+        for l in input {
+            let Ok(line) = l else {
+                break;
+            };
+            let Some(command) = skip_unknown_tokens(&line) else {
+                if !line.trim().is_empty() {
+                    report(UciError::UnknownCommand(line.trim().to_string()));
+                }
+                continue;
+            };
+            match command {
+                "uci" => {
+                    println!("id name Ferrous {}\r", env!("CARGO_PKG_VERSION"));
+                    println!("id author Sava2008\r");
+                    print_options();
+                    println!("uciok\r");
+                }
+                "isready" => println!("readyok\r"),
+                "stop" => {
+                    signals.stop.store(true, Ordering::Relaxed);
+                    wait_for_search(&mut search_thread, &mut idle_engine);
+                }
+                "ponderhit" => {
+                    signals
+                        .ponderhit_ms
+                        .store(search_start.elapsed().as_millis() as u64, Ordering::Relaxed);
+                    signals.pondering.store(false, Ordering::Relaxed);
+                }
+                "quit" => {
+                    signals.stop.store(true, Ordering::Relaxed);
+                    wait_for_search(&mut search_thread, &mut idle_engine);
+                    break;
+                }
+                "ucinewgame" => {
+                    stop_search(&signals, &mut search_thread, &mut idle_engine);
+                    idle_engine.as_deref_mut().unwrap().new_game();
+                }
+
+                _ => {
+                    let command_name: &str = command.split_whitespace().next().unwrap();
+                    if command_name == "setoption" {
+                        // joining an infinite search would hang, so the option is refused
+                        if search_thread.as_ref().is_some_and(|h| !h.is_finished()) {
+                            report(UciError::SearchRunning(command_name.to_string()));
+                            continue;
+                        }
+                        wait_for_search(&mut search_thread, &mut idle_engine);
+                        if let Err(e) = set_option(idle_engine.as_deref_mut().unwrap(), command) {
+                            report(e);
+                        }
+                    } else if command_name == "position" {
+                        // the previous position stays when the new one is invalid
+                        match parse_position(command) {
+                            Ok((raw_board, raw_state)) => {
+                                (board, state) = (Some(raw_board), Some(raw_state))
+                            }
+                            Err(e) => report(e),
+                        }
+                    } else if command_name == "bench" {
+                        // "bench [depth]", a fixed suite for comparing node counts between builds
+                        if search_thread.as_ref().is_some_and(|h| !h.is_finished()) {
+                            report(UciError::SearchRunning(command_name.to_string()));
+                            continue;
+                        }
+                        wait_for_search(&mut search_thread, &mut idle_engine);
+                        let mut split_command: Peekable<SplitWhitespace<'_>> =
+                            command.split_whitespace().peekable();
+                        split_command.next();
+                        let depth: u8 = match split_command.peek() {
+                            Some(_) => parse_go_value::<u8>("depth", &mut split_command)
+                                .unwrap_or(BENCH_DEPTH)
+                                .clamp(1, MAX_SEARCH_DEPTH),
+                            None => BENCH_DEPTH,
+                        };
+                        let engine: &mut Engine = idle_engine.take().unwrap();
+                        idle_engine = Some(
+                            std::thread::Builder::new()
+                                .stack_size(SEARCH_THREAD_STACK_SIZE)
+                                .spawn_scoped(scope, move || {
+                                    run_guarded(engine, |e| run_bench(e, depth));
+                                    engine
+                                })
+                                .unwrap()
+                                .join()
+                                .unwrap(),
+                        );
+                    } else if command_name == "go" {
+                        // a new "go" replaces the running search, which may never end by itself
+                        stop_search(&signals, &mut search_thread, &mut idle_engine);
+                        let (mut b, mut s) = if let Some(temp_board) = board.clone()
+                            && let Some(temp_state) = state.clone()
+                        {
+                            (temp_board, temp_state)
+                        } else {
+                            fen_to_board(STARTING_POSITION_FEN)
+                        };
+                        b.total_occupancy();
+                        b.update_full_cache();
+
+                        let mut split_command: Peekable<SplitWhitespace<'_>> =
+                            command.split_whitespace().peekable();
+                        split_command.next();
+                        let mut max_depth: Option<u8> = None;
+                        let (mut nodes, mut mate): (Option<u64>, Option<u8>) = (None, None);
+                        let mut search_moves: Vec<u16> = Vec::new();
+                        let (mut perft, mut infinite, mut ponder) = (false, false, false);
+                        let (mut white_time, mut black_time, mut moves_to_go, mut move_time): (
+                            Option<u64>,
+                            Option<u64>,
+                            Option<u64>,
+                            Option<u64>,
+                        ) = (None, None, None, None);
+                        let (mut white_increment, mut black_increment): (u64, u64) = (0, 0);
+                        while let Some(restriction) = split_command.next() {
+                            match restriction {
+                                "depth" => {
+                                    if let Some(value) = parse_go_value("depth", &mut split_command)
+                                    {
+                                        max_depth = Some(value);
+                                    }
+                                }
+                                "movetime" => {
+                                    if let Some(value) =
+                                        parse_go_value("movetime", &mut split_command)
+                                    {
+                                        move_time = Some(value);
+                                    }
+                                }
+                                "wtime" => {
+                                    if let Some(value) = parse_go_value("wtime", &mut split_command)
+                                    {
+                                        white_time = Some(value);
+                                    }
+                                }
+                                "btime" => {
+                                    if let Some(value) = parse_go_value("btime", &mut split_command)
+                                    {
+                                        black_time = Some(value);
+                                    }
+                                }
+                                "winc" => {
+                                    if let Some(value) = parse_go_value("winc", &mut split_command)
+                                    {
+                                        white_increment = value;
+                                    }
+                                }
+                                "binc" => {
+                                    if let Some(value) = parse_go_value("binc", &mut split_command)
+                                    {
+                                        black_increment = value;
+                                    }
+                                }
+                                "movestogo" => {
+                                    if let Some(value) =
+                                        parse_go_value("movestogo", &mut split_command)
+                                    {
+                                        moves_to_go = Some(value);
+                                    }
+                                }
+                                "perft" => {
+                                    if let Some(value) = parse_go_value("perft", &mut split_command)
+                                    {
+                                        perft = true;
+                                        max_depth = Some(value);
+                                    }
+                                }
+                                "nodes" => {
+                                    if let Some(value) = parse_go_value("nodes", &mut split_command)
+                                    {
+                                        nodes = Some(value);
+                                    }
+                                }
+                                "mate" => {
+                                    if let Some(value) = parse_go_value("mate", &mut split_command)
+                                    {
+                                        mate = Some(value);
+                                    }
+                                }
+                                "searchmoves" => {
+                                    // the list ends at the first token that is not a move
+                                    while let Some(m) = split_command
+                                        .peek()
+                                        .and_then(|token| uci_to_move(&b, &s, token).ok())
+                                    {
+                                        search_moves.push(m);
+                                        split_command.next();
+                                    }
+                                }
+                                "infinite" => infinite = true,
+                                "ponder" => ponder = true,
+                                _ => (), // unknown tokens are ignored
+                            }
+                        }
+
+                        let (time_left, increment): (Option<u64>, u64) = if s.whose_turn == 8 {
+                            (white_time, white_increment)
+                        } else {
+                            (black_time, black_increment)
+                        };
+                        let engine: &mut Engine = idle_engine.take().unwrap();
+                        let time_manager: TimeManager = if let Some(ms) = move_time {
+                            TimeManager::from_move_time(ms, engine.move_overhead_ms)
+                        } else if let Some(ms) = time_left {
+                            TimeManager::from_clock(
+                                ms,
+                                increment,
+                                moves_to_go,
+                                engine.move_overhead_ms,
+                            )
+                        } else {
+                            TimeManager::infinite()
+                        };
+                        let max_depth: u8 = max_depth.unwrap_or(
+                            if infinite
+                                || move_time.is_some()
+                                || time_left.is_some()
+                                || nodes.is_some()
+                                || mate.is_some()
+                            {
+                                MAX_SEARCH_DEPTH
+                            } else {
+                                10
+                            },
+                        );
+
+                        if perft {
+                            idle_engine = Some(engine);
+                            let color: u16 = s.whose_turn;
+                            b.calculate_check_restrictions(&mut s, color);
+                            tests::perft::run_perft(b, s, max_depth);
+                            continue;
+                        }
+
+                        let max_depth: u8 = max_depth.clamp(1, MAX_SEARCH_DEPTH);
+                        signals.stop.store(false, Ordering::Relaxed);
+                        signals.infinite.store(infinite, Ordering::Relaxed);
+                        signals.pondering.store(ponder, Ordering::Relaxed);
+                        signals.ponderhit_ms.store(0, Ordering::Relaxed);
+                        search_start = time_manager.start_time;
+
+                        engine.side = s.whose_turn;
+                        engine.depth = max_depth;
+                        let limits: SearchLimits = SearchLimits {
+                            depth: max_depth,
+                            nodes,
+                            mate,
+                            time_manager,
+                            search_moves,
+                        };
+                        search_thread = Some(
+                            std::thread::Builder::new()
+                                .stack_size(SEARCH_THREAD_STACK_SIZE)
+                                .spawn_scoped(scope, move || {
+                                    let engine_move: Option<u16> = run_guarded(engine, |e| {
+                                        e.find_best_move(&b, &mut s, limits)
+                                    })
+                                    .flatten();
+                                    // "go infinite" and "go ponder" answer only after "stop" or "ponderhit"
+                                    while engine.signals.must_wait() {
+                                        std::thread::sleep(Duration::from_millis(1));
+                                    }
+                                    let ponder_move: String = match engine.pv_lines.first() {
+                                        Some(l)
+                                            if l.line.len() > 1
+                                                && Some(l.line[0]) == engine_move =>
+                                        {
+                                            format!(
+                                                " ponder {}",
+                                                move_to_uci(l.line[1], engine.chess960)
+                                            )
+                                        }
+                                        _ => String::new(),
+                                    };
+                                    println!(
+                                        "bestmove {}{ponder_move}\r",
+                                        engine_move.map_or("0000".to_string(), |m| move_to_uci(
+                                            m,
+                                            engine.chess960
+                                        ))
+                                    );
+                                    engine
+                                })
+                                .unwrap(),
+                        );
+                    }
+                }
+            }
+        }
+    });
+}
+
+// "go infinite" and "go ponder" end only on "stop", so the search is stopped before joining it
+fn stop_search<'scope, 'env>(
+    signals: &SearchSignals,
+    search_thread: &mut Option<ScopedJoinHandle<'scope, &'env mut Engine>>,
+    idle_engine: &mut Option<&'env mut Engine>,
+) -> () {
+    if search_thread.is_some() {
+        signals.stop.store(true, Ordering::Relaxed);
+    }
+    wait_for_search(search_thread, idle_engine);
+}
+
+// the search threads run through it, so that the engine they borrow always comes back:
+// a panic is reported and the engine forgets the game, its tables may be half updated
+fn run_guarded<T>(engine: &mut Engine, search: impl FnOnce(&mut Engine) -> T) -> Option<T> {
+    match std::panic::catch_unwind(AssertUnwindSafe(|| search(&mut *engine))) {
+        Ok(result) => return Some(result),
+        Err(payload) => {
+            let reason: &str = if let Some(message) = payload.downcast_ref::<&str>() {
+                message
+            } else if let Some(message) = payload.downcast_ref::<String>() {
+                message
+            } else {
+                "unknown panic"
+            };
+            report(UciError::SearchFailed(reason.to_string()));
+            engine.new_game();
+            return None;
+        }
+    }
+}
+
+fn wait_for_search<'scope, 'env>(
+    search_thread: &mut Option<ScopedJoinHandle<'scope, &'env mut Engine>>,
+    idle_engine: &mut Option<&'env mut Engine>,
+) -> () {
+    if let Some(handle) = search_thread.take() {
+        match handle.join() {
+            Ok(engine) => *idle_engine = Some(engine),
+            // a panic outside run_guarded can only come from writing to the GUI, so there
+            // is no one left to answer
+            Err(_) => {
+                report(UciError::SearchFailed("search thread".to_string()));
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+// "position startpos [moves ...]" or "position fen <fen> [moves ...]"
+pub fn parse_position(command: &str) -> Result<(Board, GameState), UciError> {
+    let mut tokens: std::str::SplitWhitespace<'_> = command.split_whitespace();
+    tokens.next(); // "position"
+    let (mut board, mut state): (Board, GameState) = match tokens.next() {
+        Some("startpos") => {
+            tokens.next(); // "moves"
+            fen_to_board(STARTING_POSITION_FEN)
+        }
+        Some("fen") => {
+            let fen_fields: Vec<&str> = tokens.by_ref().take_while(|t| *t != "moves").collect();
+            parse_fen(fen_fields.join(" ").as_str())?
+        }
+        _ => {
+            return Err(UciError::MissingValue(
+                "position startpos or fen".to_string(),
+            ));
+        }
+    };
+
+    for uci_move in tokens {
+        let m: u16 = uci_to_move(&board, &state, uci_move)?;
+        apply_game_move(&mut board, &mut state, m);
+    }
+    return Ok((board, state));
+}
+
+// the GUI may put unknown tokens before the command, they are skipped
+fn skip_unknown_tokens(line: &str) -> Option<&str> {
+    let mut rest: &str = line.trim();
+    while let Some(token) = rest.split_whitespace().next() {
+        if UCI_COMMANDS.contains(&token) {
+            return Some(rest);
+        }
+        rest = rest[token.len()..].trim_start();
+    }
+    return None;
+}
+
+// the value after a "go" parameter. a bad value is reported and left
+// in place, so that it is read as the next parameter or ignored
+fn parse_go_value<T: FromStr>(
+    parameter: &str,
+    tokens: &mut Peekable<SplitWhitespace<'_>>,
+) -> Option<T> {
+    let Some(value) = tokens.peek() else {
+        report(UciError::MissingValue(parameter.to_string()));
+        return None;
+    };
+    if let Ok(parsed) = value.parse::<T>() {
+        tokens.next();
+        return Some(parsed);
+    }
+    report(UciError::InvalidValue {
+        parameter: parameter.to_string(),
+        value: value.to_string(),
+    });
+    return None;
+}
+
+fn report(error: UciError) -> () {
+    println!("info string error: {error}\r");
+}
+
+// plays a move of the actual game, keeping the history needed for repetitions and the 50-move rule
+fn apply_game_move(board: &mut Board, state: &mut GameState, m: u16) -> () {
+    let color: u16 = state.whose_turn;
+    let mut current_hash: u64 = Engine::rebuild_hash(board, state, color);
+    board.perform_move(m, state, color, &mut 0, &mut current_hash);
+    if state.fifty_moves_rule_counter == 0 {
+        // previous positions cannot occur again
+        state.irreversible_moves.clear();
+    }
+
+    if color == 16 {
+        state.total_moves_amount = state.total_moves_amount.saturating_add(1);
+    }
+    state.whose_turn = if color == 8 { 16 } else { 8 };
+}
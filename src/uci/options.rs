@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use crate::{
+    employ_config::{load_opening_book, load_opening_book_file},
+    search::{DEFAULT_CONTEMPT, Engine},
+    time_management::MOVE_OVERHEAD_MS,
+    transposition::{DEFAULT_HASH_MB, TranspositionTable},
+    uci::error::UciError,
+};
+
+pub const EMPTY_STRING_OPTION: &str = "<empty>";
+
+pub enum UciOptionType {
+    Spin { default: i64, min: i64, max: i64 },
+    Check { default: bool },
+    String { default: &'static str },
+    Button,
+}
+
+pub enum UciOptionValue {
+    Spin(i64),
+    Check(bool),
+    String(String),
+    Button,
+}
+
+pub struct UciOption {
+    pub name: &'static str,
+    pub option_type: UciOptionType,
+}
+
+pub const UCI_OPTIONS: [UciOption; 9] = [
+    UciOption {
+        name: "Hash",
+        option_type: UciOptionType::Spin {
+            default: DEFAULT_HASH_MB as i64,
+            min: 1,
+            max: 65536,
+        },
+    },
+    UciOption {
+        name: "Clear Hash",
+        option_type: UciOptionType::Button,
+    },
+    UciOption {
+        name: "Threads",
+        option_type: UciOptionType::Spin {
+            default: 1,
+            min: 1,
+            max: 256,
+        },
+    },
+    UciOption {
+        name: "MultiPV",
+        option_type: UciOptionType::Spin {
+            default: 1,
+            min: 1,
+            max: 256,
+        },
+    },
+    UciOption {
+        name: "OwnBook",
+        option_type: UciOptionType::Check { default: true },
+    },
+    UciOption {
+        name: "BookFile",
+        option_type: UciOptionType::String {
+            default: EMPTY_STRING_OPTION, // the book embedded in the executable
+        },
+    },
+    UciOption {
+        name: "Move Overhead",
+        option_type: UciOptionType::Spin {
+            default: MOVE_OVERHEAD_MS as i64,
+            min: 0,
+            max: 5000,
+        },
+    },
+    UciOption {
+        name: "Contempt",
+        option_type: UciOptionType::Spin {
+            default: DEFAULT_CONTEMPT as i64,
+            min: -100,
+            max: 100,
+        },
+    },
+    UciOption {
+        name: "UCI_Chess960",
+        option_type: UciOptionType::Check { default: false },
+    },
+];
+
+impl UciOption {
+    // the line sent as an answer to "uci"
+    pub fn declaration(&self) -> String {
+        return match &self.option_type {
+            UciOptionType::Spin { default, min, max } => format!(
+                "option name {} type spin default {default} min {min} max {max}",
+                self.name
+            ),
+            UciOptionType::Check { default } => {
+                format!("option name {} type check default {default}", self.name)
+            }
+            UciOptionType::String { default } => {
+                format!("option name {} type string default {default}", self.name)
+            }
+            UciOptionType::Button => format!("option name {} type button", self.name),
+        };
+    }
+
+    pub fn parse_value(&self, value: Option<&str>) -> Result<UciOptionValue, UciError> {
+        return match (&self.option_type, value) {
+            (UciOptionType::Button, _) => Ok(UciOptionValue::Button),
+            (_, None) => Err(UciError::MissingValue(format!("option {}", self.name))),
+            (UciOptionType::Spin { min, max, .. }, Some(v)) => match v.parse::<i64>() {
+                Ok(number) if number >= *min && number <= *max => Ok(UciOptionValue::Spin(number)),
+                _ => Err(UciError::InvalidOption(format!(
+                    "option {} expects an integer from {min} to {max}, got {v}",
+                    self.name
+                ))),
+            },
+            (UciOptionType::Check { .. }, Some(v)) => match v.to_ascii_lowercase().as_str() {
+                "true" => Ok(UciOptionValue::Check(true)),
+                "false" => Ok(UciOptionValue::Check(false)),
+                _ => Err(UciError::InvalidOption(format!(
+                    "option {} expects true or false, got {v}",
+                    self.name
+                ))),
+            },
+            (UciOptionType::String { .. }, Some(v)) => Ok(UciOptionValue::String(v.to_string())),
+        };
+    }
+}
+
+pub fn print_options() -> () {
+    for option in UCI_OPTIONS.iter() {
+        println!("{}\r", option.declaration());
+    }
+}
+
+// "setoption name <id> [value <x>]", both the name and the value may contain spaces
+pub fn set_option(engine: &mut Engine, command: &str) -> Result<(), UciError> {
+    let mut tokens: std::str::SplitWhitespace<'_> = command.split_whitespace();
+    tokens.next(); // "setoption"
+    if tokens.next() != Some("name") {
+        return Err(UciError::MissingValue("setoption name".to_string()));
+    }
+    let name: String = tokens
+        .by_ref()
+        .take_while(|t| *t != "value")
+        .collect::<Vec<&str>>()
+        .join(" ");
+    let value: String = tokens.collect::<Vec<&str>>().join(" ");
+
+    let option: &UciOption = UCI_OPTIONS
+        .iter()
+        .find(|o| o.name.eq_ignore_ascii_case(&name))
+        .ok_or_else(|| UciError::InvalidOption(format!("unknown option {name}")))?;
+    let parsed_value: UciOptionValue =
+        option.parse_value(if value.is_empty() { None } else { Some(&value) })?;
+
+    match (option.name, parsed_value) {
+        ("Hash", UciOptionValue::Spin(size_mb)) => {
+            // the old table stays when the new one cannot be allocated
+            engine.transposition_table = Arc::new(
+                TranspositionTable::try_new(size_mb as usize).map_err(UciError::InvalidOption)?,
+            );
+        }
+        ("Clear Hash", UciOptionValue::Button) => engine.transposition_table.clear(),
+        ("Threads", UciOptionValue::Spin(threads)) => engine.threads = threads as usize,
+        ("MultiPV", UciOptionValue::Spin(lines)) => engine.multi_pv = lines as usize,
+        ("OwnBook", UciOptionValue::Check(own_book)) => engine.own_book = own_book,
+        ("BookFile", UciOptionValue::String(path)) => {
+            engine.opening_book = if path == EMPTY_STRING_OPTION {
+                load_opening_book()
+            } else {
+                load_opening_book_file(&path).map_err(UciError::InvalidOption)?
+            };
+        }
+        ("Move Overhead", UciOptionValue::Spin(overhead)) => {
+            engine.move_overhead_ms = overhead as u64
+        }
+        ("Contempt", UciOptionValue::Spin(contempt)) => engine.contempt = contempt as i32,
+        ("UCI_Chess960", UciOptionValue::Check(chess960)) => engine.chess960 = chess960,
+        _ => unreachable!(),
+    }
+    return Ok(());
+}